@@ -0,0 +1,64 @@
+use chessr::{Board, Move};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const MIDGAME_POSITION: &str = "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP2BPPP/R2QKB1R w KQ - 2 8";
+
+/// Counts the leaf nodes of the legal move tree up to the given depth.
+fn perft(board: &Board, depth: u32) -> u64 {
+    let legal_moves = board.legal_moves();
+
+    if depth <= 1 {
+        return legal_moves.len() as u64;
+    }
+
+    legal_moves
+        .iter()
+        .map(|r#move| {
+            let mut board = board.clone();
+            board.push(r#move);
+            perft(&board, depth - 1)
+        })
+        .sum()
+}
+
+fn bench_legal_moves(c: &mut Criterion) {
+    let startpos = Board::new();
+    let midgame = Board::from_fen(MIDGAME_POSITION).unwrap();
+
+    // cloning a board drops its cache of legal moves, so every iteration
+    // generates them instead of returning the cached ones
+    c.bench_function("legal_moves startpos", |b| {
+        b.iter(|| black_box(startpos.clone()).legal_moves())
+    });
+
+    c.bench_function("legal_moves midgame", |b| {
+        b.iter(|| black_box(midgame.clone()).legal_moves())
+    });
+}
+
+fn bench_perft(c: &mut Criterion) {
+    let board = Board::new();
+
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+    group.bench_function("perft(4) startpos", |b| {
+        b.iter(|| perft(black_box(&board), 4))
+    });
+    group.finish();
+}
+
+fn bench_from_san(c: &mut Criterion) {
+    let board = Board::from_fen(MIDGAME_POSITION).unwrap();
+    let moves = ["O-O", "Qb3", "cxd5", "Nxd5", "a3", "Bd3", "Rc1", "h4"];
+
+    c.bench_function("Move::from_san midgame", |b| {
+        b.iter(|| {
+            for r#move in &moves {
+                black_box(Move::from_san(black_box(r#move), &board));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_legal_moves, bench_perft, bench_from_san);
+criterion_main!(benches);
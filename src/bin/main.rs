@@ -3,7 +3,7 @@ use std::io::{stdin, stdout, Write};
 use std::time::Instant;
 
 use anyhow::Result;
-use chessr::Board;
+use chessr::{Board, NoMovesReason};
 use rand::random;
 
 const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
@@ -52,11 +52,15 @@ fn play(startpos: &str) -> Result<()> {
     println!();
 
     loop {
-        if board.checkmate() {
-            println!("Checkmate");
-            break;
-        } else if board.draw() {
-            println!("Draw");
+        let result = match board.no_moves_reason() {
+            Some(NoMovesReason::Checkmate) => Some("Checkmate"),
+            Some(NoMovesReason::Stalemate) => Some("Draw"),
+            None if board.draw() => Some("Draw"),
+            None => None,
+        };
+
+        if let Some(result) = result {
+            println!("{}", result);
             break;
         }
 
@@ -65,7 +69,7 @@ fn play(startpos: &str) -> Result<()> {
         stdout().flush()?;
         stdin().read_line(&mut r#move)?;
         let start = Instant::now();
-        let made_move = board.make_move(r#move.trim());
+        let made_move = board.make_move_default_promotion(r#move.trim());
         if made_move.is_none() {
             continue;
         }
@@ -101,12 +105,15 @@ fn random_game() -> Result<()> {
     let mut total_moves = 0;
 
     loop {
-        if board.checkmate() {
-            println!("Checkmate");
-            println!("Average Time per Move: {}μs", total_time / total_moves);
-            break;
-        } else if board.draw() {
-            println!("Draw");
+        let result = match board.no_moves_reason() {
+            Some(NoMovesReason::Checkmate) => Some("Checkmate"),
+            Some(NoMovesReason::Stalemate) => Some("Draw"),
+            None if board.draw() => Some("Draw"),
+            None => None,
+        };
+
+        if let Some(result) = result {
+            println!("{}", result);
             println!("Average Time per Move: {}μs", total_time / total_moves);
             break;
         }
@@ -2,26 +2,38 @@
 // FEN position strings
 pub const FEN_STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
-// Regex patterns for algebraic notation
-pub const CASTLE_REGEX: &str = r"^(O-O|O-O-O|0-0|0-0-0|o-o|o-o-o)(\+|\#)?$";
-pub const PAWN_MOVE_REGEX: &str = r"^([a-h])([2-7])(\+|\#)?$";
-pub const PIECE_MOVE_REGEX: &str = r"^([KQBNR])([a-h])([1-8])(\+|\#)?$";
-pub const PAWN_CAPTURE_REGEX: &str = r"^([a-h])x([a-h])([2-7])(\+|\#)?$";
-pub const PIECE_CAPTURE_REGEX: &str = r"^([KQBNR])x([a-h])([1-8])(\+|\#)?$";
-pub const PAWN_PROMOTION_REGEX: &str = r"^([a-h])(1|8)=([QBNR])(\+|\#)?$";
-pub const PAWN_CAPTURE_PROMOTION_REGEX: &str = r"^([a-h])x([a-h])(1|8)=([QBNR])(\+|\#)?$";
-pub const PIECE_MOVE_ROW_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([1-8])([a-h])([1-8])(\+|\#)?$";
-pub const PIECE_MOVE_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])([a-h])([1-8])(\+|\#)?$";
-pub const PIECE_MOVE_ROW_AND_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])([1-8])([a-h])([1-8])(\+|\#)?$";
-pub const PIECE_CAPTURE_ROW_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([1-8])x([a-h])([1-8])(\+|\#)?$";
-pub const PIECE_CAPTURE_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])x([a-h])([1-8])(\+|\#)?$";
-pub const PIECE_CAPTURE_ROW_AND_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])([1-8])x([a-h])([1-8])(\+|\#)?$";
+// Regex patterns for algebraic notation. A trailing "++" (used by some sources
+// for double check) is accepted and ignored like "+" and "#".
+pub const CASTLE_REGEX: &str = r"^(O-O|O-O-O|0-0|0-0-0|o-o|o-o-o)(\+\+|\+|\#)?$";
+pub const PAWN_MOVE_REGEX: &str = r"^([a-h])([2-7])(\+\+|\+|\#)?$";
+pub const PIECE_MOVE_REGEX: &str = r"^([KQBNR])([a-h])([1-8])(\+\+|\+|\#)?$";
+pub const PAWN_CAPTURE_REGEX: &str = r"^([a-h])x([a-h])([2-7])(\+\+|\+|\#)?$";
+pub const PIECE_CAPTURE_REGEX: &str = r"^([KQBNR])x([a-h])([1-8])(\+\+|\+|\#)?$";
+pub const PAWN_PROMOTION_REGEX: &str = r"^([a-h])(1|8)=([QBNR])(\+\+|\+|\#)?$";
+pub const PAWN_CAPTURE_PROMOTION_REGEX: &str = r"^([a-h])x([a-h])(1|8)=([QBNR])(\+\+|\+|\#)?$";
+pub const PIECE_MOVE_ROW_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([1-8])([a-h])([1-8])(\+\+|\+|\#)?$";
+pub const PIECE_MOVE_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])([a-h])([1-8])(\+\+|\+|\#)?$";
+pub const PIECE_MOVE_ROW_AND_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])([1-8])([a-h])([1-8])(\+\+|\+|\#)?$";
+pub const PIECE_CAPTURE_ROW_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([1-8])x([a-h])([1-8])(\+\+|\+|\#)?$";
+pub const PIECE_CAPTURE_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])x([a-h])([1-8])(\+\+|\+|\#)?$";
+pub const PIECE_CAPTURE_ROW_AND_COLUMN_DISAMBIGUATION_REGEX: &str = r"^([KQBNR])([a-h])([1-8])x([a-h])([1-8])(\+\+|\+|\#)?$";
 
-// Regex patterns for UCI notation
-pub const UCI_MOVE_REGEX: &str = r"^([a-h])([1-8])([a-h])([1-8])([qrbn]?)$";
-pub const UCI_MOVE_DASH_REGEX: &str = r"^([a-h])([1-8])-([a-h])([1-8])([qrbn]?)$";
+// Regex patterns for UCI notation. The promotion piece is accepted in either
+// case and may be preceded by '=' (e.g. "e7e8q", "e7e8Q" or "e7e8=Q").
+pub const UCI_MOVE_REGEX: &str = r"^([a-h])([1-8])([a-h])([1-8])(?:=?([qrbnQRBN]))?$";
+pub const UCI_MOVE_DASH_REGEX: &str = r"^([a-h])([1-8])-([a-h])([1-8])(?:=?([qrbnQRBN]))?$";
 
 
+// Flags of the 16-bit move encoding, stored in its 4 highest bits. Promotions
+// set the promotion bit and keep the promotion piece in the 2 lowest bits of
+// the flag; captures, promotions included, set the capture bit.
+pub const MOVE_FLAG_QUIET: u16 = 0;
+pub const MOVE_FLAG_DOUBLE_PAWN_PUSH: u16 = 1;
+pub const MOVE_FLAG_KINGSIDE_CASTLE: u16 = 2;
+pub const MOVE_FLAG_QUEENSIDE_CASTLE: u16 = 3;
+pub const MOVE_FLAG_CAPTURE: u16 = 4;
+pub const MOVE_FLAG_PROMOTION: u16 = 8;
+
 // Pieces move directions
 pub const PAWN_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (2, 0), (1, 1), (1, -1)];
 pub const PAWN_CAPTURE_DIRECTIONS: [(i8, i8); 2] = [(1, 1), (1, -1)];
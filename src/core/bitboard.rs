@@ -0,0 +1,69 @@
+use crate::core::SquareCoords;
+
+/// Represents a set of squares as a 64-bit mask, where bit `n` is set if the
+/// square with [index](crate::SquareCoords::index) `n` (a1 = 0, h8 = 63) is
+/// part of the set.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitBoard(pub u64);
+
+impl BitBoard {
+    /// Returns the number of squares in the set.
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Returns true if the given square is part of the set.
+    pub fn contains(&self, square: SquareCoords) -> bool {
+        square.inside_board() && self.0 & (1 << square.index()) != 0
+    }
+
+    /// Returns an iterator over the squares in the set, from a1 to h8. Only
+    /// the set bits are visited, by repeatedly taking the lowest one.
+    pub fn iter_squares(&self) -> impl Iterator<Item = SquareCoords> {
+        let mut bits = self.0;
+
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+
+            let index = bits.trailing_zeros() as usize;
+            bits &= bits - 1; // clear the lowest set bit
+
+            SquareCoords::from_index(index)
+        })
+    }
+}
+
+impl FromIterator<SquareCoords> for BitBoard {
+    fn from_iter<I: IntoIterator<Item = SquareCoords>>(squares: I) -> Self {
+        BitBoard(
+            squares
+                .into_iter()
+                .filter(|square| square.inside_board())
+                .fold(0, |bits, square| bits | 1 << square.index()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_iter_squares() {
+        let squares = [
+            SquareCoords::from_san_str("a1").unwrap(),
+            SquareCoords::from_san_str("e4").unwrap(),
+            SquareCoords::from_san_str("h8").unwrap(),
+        ];
+        let bitboard = squares.iter().copied().collect::<BitBoard>();
+
+        assert_eq!(bitboard, BitBoard(1 | 1 << 28 | 1 << 63));
+        assert_eq!(bitboard.count(), 3);
+        assert_eq!(bitboard.iter_squares().collect::<Vec<_>>(), squares);
+        assert!(bitboard.contains(squares[1]));
+        assert!(!bitboard.contains(SquareCoords::from_san_str("e5").unwrap()));
+        assert_eq!(BitBoard::default().iter_squares().count(), 0);
+    }
+}
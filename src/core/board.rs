@@ -1,13 +1,99 @@
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
-use crate::constants::{FEN_STARTING_POSITION, PAWN_CAPTURE_DIRECTIONS};
-use crate::core::{movegen, CastleKind, CastleRights, Color, Move, Piece, SquareCoords};
+use rand::Rng;
+
+use crate::constants::FEN_STARTING_POSITION;
+use crate::core::{
+    movegen, BitBoard, CastleKind, CastleRights, Color, Move, NoMovesReason, Outcome, Piece,
+    PieceKind, SanLanguage, SquareCoords,
+};
 use crate::fen::{self, FenParseError};
 
+/// Represents errors that can occur when playing a move with
+/// [Board::play()](crate::Board::play()).
+#[derive(Debug)]
+pub enum PlayError {
+    /// The game was already over before the move.
+    GameOver(Outcome),
+    /// The move couldn't be read as a move in the current position.
+    InvalidMove,
+    /// The move was read but it is not legal in the current position.
+    IllegalMove,
+}
+
+impl std::error::Error for PlayError {}
+
+impl std::fmt::Display for PlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlayError::GameOver(outcome) => write!(f, "Game over: {}", outcome),
+            PlayError::InvalidMove => write!(f, "Invalid move"),
+            PlayError::IllegalMove => write!(f, "Illegal move"),
+        }
+    }
+}
+
+/// Represents the ways the pieces on a board can be impossible to reach in a
+/// game, see [Board::validate_material()](crate::Board::validate_material()).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    /// The side has more than 16 pieces.
+    TooManyPieces(Color),
+    /// The side has more than 8 pawns.
+    TooManyPawns(Color),
+}
+
+impl std::error::Error for BoardError {}
+
+impl std::fmt::Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BoardError::TooManyPieces(color) => write!(f, "{} has more than 16 pieces", color),
+            BoardError::TooManyPawns(color) => write!(f, "{} has more than 8 pawns", color),
+        }
+    }
+}
+
+/// The legal moves of a position where the king is in check, grouped by the
+/// way they get out of it. See
+/// [Board::check_escapes()](crate::Board::check_escapes()).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckEscapes {
+    /// Moves of the king, including the ones capturing the checking piece.
+    pub king_moves: Vec<Move>,
+
+    /// Moves of other pieces capturing the checking piece.
+    pub captures_of_checker: Vec<Move>,
+
+    /// Moves placing a piece between the king and the checking piece.
+    pub blocks: Vec<Move>,
+}
+
+/// Drawing styles of the board, see
+/// [Board::render()](crate::Board::render()).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum RenderStyle {
+    /// Unicode chess pieces inside a box-drawing grid, as printed by the
+    /// `Display` implementation of [Board].
+    #[default]
+    Unicode,
+    /// FEN piece letters inside a grid drawn with `+`, `-` and `|`.
+    AsciiLetters,
+    /// Unicode chess pieces inside a grid drawn with `+`, `-` and `|`.
+    UnicodePiecesAsciiGrid,
+}
+
 /// Represents a chess board.
 ///
 /// The board is represented as an 8x8 array of [Piece]. Each piece is an
 /// optional value, where `None` represents an empty square.
+///
+/// The legal moves of the current position are cached after the first call to
+/// [legal_moves()](crate::Board::legal_moves()). The cache is a `OnceLock`, so
+/// `Board` is still `Send` and `Sync` and can be read from several threads at
+/// once; if two threads fill the cache at the same time one of them just does
+/// the work for nothing. Making a move requires `&mut self` and resets it.
 #[derive(Debug, Clone)]
 pub struct Board {
     /// Board squares represented either by a [Piece] or `None` if the square
@@ -31,6 +117,80 @@ pub struct Board {
 
     /// History of the board's positions.
     pub position_history: Vec<String>,
+
+    /// Whether the white king has left its starting square at some point.
+    pub white_king_moved: bool,
+
+    /// Whether the black king has left its starting square at some point.
+    pub black_king_moved: bool,
+
+    /// Legal moves of the last position they were generated for.
+    pub(crate) legal_moves_cache: LegalMovesCache,
+
+    /// What each move made on the board changed, so it can be taken back with
+    /// [undo()](crate::Board::undo()). `None` until
+    /// [enable_undo()](crate::Board::enable_undo()) is called.
+    pub(crate) undo_stack: Option<Vec<Undo>>,
+}
+
+/// The state a move overwrites, stored so the move can be taken back.
+#[derive(Debug, Clone)]
+pub(crate) struct Undo {
+    r#move: Move,
+    /// Piece captured by the move and the square it was on, which is not the
+    /// destination square for en passant captures.
+    captured: Option<(SquareCoords, Piece)>,
+    /// Castle rights before the move, only stored if the move could change
+    /// them.
+    castle_rights: Option<Vec<CastleRights>>,
+    en_passant_target: Option<SquareCoords>,
+    halfmove_clock: u32,
+    white_king_moved: bool,
+    black_king_moved: bool,
+}
+
+/// Canonical view of a position, see [Board::position_identity].
+type PositionIdentity = ([[Option<Piece>; 8]; 8], Color, u8, Option<SquareCoords>);
+
+/// Cache of the legal moves of a board together with the position they were
+/// generated for. The position is checked on every lookup, so the cache is
+/// never used for a different position even if the public fields of the board
+/// are modified directly. Cloning a board doesn't clone its cache, since
+/// clones are mostly made to try out moves.
+#[derive(Default)]
+pub(crate) struct LegalMovesCache(OnceLock<(PositionIdentity, Vec<Move>)>);
+
+impl LegalMovesCache {
+    /// Returns the cached moves if they were generated for the given position.
+    fn get(&self, identity: &PositionIdentity) -> Option<Vec<Move>> {
+        match self.0.get() {
+            Some((cached_identity, moves)) if cached_identity == identity => Some(moves.clone()),
+            _ => None,
+        }
+    }
+
+    /// Stores the legal moves of the given position. Does nothing if the cache
+    /// is already filled, it has to be cleared first.
+    fn set(&self, identity: PositionIdentity, moves: Vec<Move>) {
+        let _ = self.0.set((identity, moves));
+    }
+
+    /// Empties the cache.
+    pub(crate) fn clear(&mut self) {
+        self.0.take();
+    }
+}
+
+impl Clone for LegalMovesCache {
+    fn clone(&self) -> Self {
+        LegalMovesCache::default()
+    }
+}
+
+impl std::fmt::Debug for LegalMovesCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LegalMovesCache")
+    }
 }
 
 impl Board {
@@ -72,89 +232,119 @@ impl Board {
         fen::fen_to_board(fen_str)
     }
 
-    /// Creates a FEN Utring representation of the current the board.
-    ///
-    /// [Forsyth–Edwards Notation](https://www.chess.com/terms/fen-chess)
-    /// (FEN) is a standard notation for describing a particular board position
-    /// of a chess game.
+    /// Same as [from_fen()](crate::Board::from_fen()), but accepts some
+    /// common mistakes of FEN generators, like a fullmove number of 0, which
+    /// is read as 1.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let board = Board::new();
-    /// assert_eq!(
-    ///     board.fen(),
-    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
-    /// );
+    /// let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0";
+    ///
+    /// assert!(Board::from_fen(fen).is_err());
+    /// assert_eq!(Board::from_fen_lenient(fen).unwrap().fullmove_number, 1);
     /// ```
-    pub fn fen(&self) -> String {
-        fen::board_to_fen(self)
+    pub fn from_fen_lenient(fen_str: &str) -> Result<Board, FenParseError> {
+        fen::fen_to_board_lenient(fen_str)
     }
 
-    /// Returns a vector of all the pieces and their respective square
-    /// coordinates that are checking the king in the current position.
+    /// Sets the board to the position of the given FEN string, as
+    /// [from_fen()](crate::Board::from_fen()) would. The vectors of castle
+    /// rights and position history are cleared and filled again instead of
+    /// replaced, so reusing a board to read many FEN strings does not
+    /// allocate for each of them. If the FEN string is not valid the board is
+    /// left unchanged.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let board = Board::from_fen("rnbqk1nr/ppp2ppp/4p3/3p4/1bPP4/5N2/PP2PPPP/RNBQKB1R w KQkq - 2 4").unwrap();
-    /// assert_eq!(board.checkers().len(), 1);
-    /// assert_eq!(board.checkers()[0].0.to_fen_char(), 'b');
-    /// assert_eq!(board.checkers()[0].1.to_string(), "b4");
-    pub fn checkers(&self) -> Vec<(Piece, SquareCoords)> {
-        self.square_attackers(self.king_square())
+    /// let mut board = Board::new();
+    /// let fen = "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 3 20";
+    ///
+    /// board.set_fen(fen).unwrap();
+    /// assert_eq!(board.fen(), fen);
+    /// assert!(board.set_fen("not a fen").is_err());
+    /// assert_eq!(board.fen(), fen);
+    /// ```
+    pub fn set_fen(&mut self, fen_str: &str) -> Result<(), FenParseError> {
+        fen::fen_to_board_in_place(self, fen_str)
     }
 
-    /// Returns true if there is a check in the current position.
+    /// Creates a board from the piece placement field of a FEN string alone,
+    /// as found in puzzles and diagrams. White moves first, castle rights are
+    /// given for every king and rook still on their starting squares, there
+    /// is no en passant target and the move counters are set to 0 and 1.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let board = Board::from_fen("rnbqk1nr/ppp2ppp/4p3/3p4/1bPP4/5N2/PP2PPPP/RNBQKB1R w KQkq - 2 4")
-    ///     .unwrap();
-    /// assert_eq!(board.check(), true);
+    /// let board = Board::from_placement("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").unwrap();
+    /// assert_eq!(
+    ///     board.fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    /// );
     /// ```
-    pub fn check(&self) -> bool {
-        !self.checkers().is_empty()
+    pub fn from_placement(placement: &str) -> Result<Board, FenParseError> {
+        fen::placement_to_board(placement)
     }
 
-    /// Returns true if there is a checkmate in the current position.
+    /// Creates a FEN Utring representation of the current the board.
+    ///
+    /// [Forsyth–Edwards Notation](https://www.chess.com/terms/fen-chess)
+    /// (FEN) is a standard notation for describing a particular board position
+    /// of a chess game.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let board =
-    ///     Board::from_fen("rnb1kbnr/pppp1ppp/4p3/8/5PPq/8/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
-    /// assert_eq!(board.checkmate(), true);
+    /// let board = Board::new();
+    /// assert_eq!(
+    ///     board.fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    /// );
     /// ```
-    pub fn checkmate(&self) -> bool {
-        self.check() && self.legal_moves().is_empty()
+    pub fn fen(&self) -> String {
+        fen::board_to_fen(self)
     }
 
-    /// Returns true if there is a stalemate in the current position.
+    /// Returns the FEN string representation of the current board, with or
+    /// without the halfmove clock and fullmove number. Without them, only the
+    /// first four fields are written, as in EPD.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let board = Board::from_fen("8/8/8/8/8/2k5/2p5/2K5 w - - 0 1").unwrap();
-    /// assert_eq!(board.stalemate(), true);
+    /// let board = Board::new();
+    /// assert_eq!(board.fen_with(true), board.fen());
+    /// assert_eq!(
+    ///     board.fen_with(false),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -"
+    /// );
     /// ```
-    pub fn stalemate(&self) -> bool {
-        !self.check() && self.legal_moves().is_empty()
+    pub fn fen_with(&self, include_counters: bool) -> String {
+        let mut fen = self.fen();
+
+        if !include_counters {
+            let len = position_key(&fen).len();
+            fen.truncate(len);
+        }
+
+        fen
     }
 
-    /// Returns true if 50 moves have been made without a pawn move or a
-    /// capture.
+    /// Appends the FEN string representation of the current board to the
+    /// given buffer. Reusing the same buffer avoids allocating a new string
+    /// each time, which matters in hot loops.
     ///
     /// # Examples
     ///
@@ -162,13 +352,22 @@ impl Board {
     /// use chessr::Board;
     ///
     /// let board = Board::new();
-    /// assert_eq!(board.fifty_move_rule(), false);
+    /// let mut buf = String::new();
+    ///
+    /// board.write_fen(&mut buf);
+    /// assert_eq!(buf, board.fen());
     /// ```
-    pub fn fifty_move_rule(&self) -> bool {
-        self.halfmove_clock >= 50
+    pub fn write_fen(&self, buf: &mut String) {
+        fen::write_fen(self, buf)
     }
 
-    /// Returns true if the current position is a draw by threefold repetition.
+    /// Returns the FEN string of the first position in the position history.
+    /// That is the position the game started from, unless the history was
+    /// started again: boards returned by
+    /// [for_search()](crate::Board::for_search()),
+    /// [flip_vertical()](crate::Board::flip_vertical()) and
+    /// [flip_horizontal()](crate::Board::flip_horizontal()) only record the
+    /// positions from their own current one.
     ///
     /// # Examples
     ///
@@ -176,550 +375,4932 @@ impl Board {
     /// use chessr::Board;
     ///
     /// let mut board = Board::new();
+    /// board.make_move("e4");
     ///
-    /// for r#move in &[
-    ///     "e4", "e5", "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8",
-    /// ] {
-    ///     board.make_move(r#move);
-    /// }
-    ///
-    /// assert_eq!(board.threefold_repetition(), true);
+    /// assert_eq!(board.initial_fen(), Board::new().fen());
     /// ```
-    pub fn threefold_repetition(&self) -> bool {
-        let mut hash_map = HashMap::new();
-
-        for pos in &self.position_history {
-            let pos: String = pos.split_whitespace().take(4).collect();
-            *hash_map.entry(pos).or_insert(0) += 1;
-        }
-
-        hash_map.iter().any(|(_, &count)| count >= 3)
+    pub fn initial_fen(&self) -> &str {
+        self.position_history
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
     }
 
-    /// Returns true if the current position is a draw by insufficient material.
+    /// Returns the board as it was after the given number of half-moves since
+    /// the first position in the position history, see
+    /// [initial_fen()](crate::Board::initial_fen()). The position history of
+    /// the returned board ends at that ply, and the moves up to that ply that
+    /// could be taken back with [undo()](crate::Board::undo()) still can be.
+    /// Returns `None` if the history doesn't reach the given ply.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let board = Board::from_fen("2k5/4b3/8/8/8/8/8/2K1B1B1 w - - 0 1").unwrap();
-    /// assert_eq!(board.insufficient_material(), true);
+    /// let mut board = Board::new();
+    /// board.make_move("e4");
+    /// board.make_move("e5");
+    ///
+    /// assert_eq!(board.goto(0).unwrap().fen(), Board::new().fen());
+    /// assert_eq!(board.goto(2).unwrap().fen(), board.fen());
+    /// assert!(board.goto(3).is_none());
     /// ```
-    pub fn insufficient_material(&self) -> bool {
-        let mut piece_count = 0;
-        let mut knights = Vec::new();
-        let mut bishops = Vec::new();
-
-        for (row_idx, &row) in self.squares.iter().enumerate() {
-            for (col_idx, &_) in row.iter().enumerate() {
-                if let Some(piece) = self.get_piece((row_idx, col_idx).into()) {
-                    match piece {
-                        Piece::Bishop(_) => {
-                            // because we need to know the color of the square in
-                            // which the bishops are, instead of pushing a piece
-                            // into the vector, we push the color of the square.
-                            let color = match (row_idx + col_idx) % 2 {
-                                0 => Color::White,
-                                _ => Color::Black,
-                            };
-                            bishops.push(color)
-                        }
-                        Piece::Knight(_) => knights.push(piece),
-                        _ => (),
-                    }
-
-                    piece_count += 1;
-                }
-            }
-        }
-
-        // king vs king
-        if piece_count == 2 {
-            return true;
-        }
+    pub fn goto(&self, ply: usize) -> Option<Board> {
+        let fen = self.position_history.get(ply)?;
+        // the positions were already accepted when they were recorded, so
+        // don't reject the material of boards read leniently
+        let mut board = fen::fen_to_board_lenient(fen).ok()?;
 
-        // king and bishop vs king or king and knight vs king
-        if piece_count == 3 && (bishops.len() == 1 || knights.len() == 1) {
-            return true;
-        }
+        board.position_history = self.position_history[..=ply].to_vec();
 
-        // king and bishop vs king and bishop with the bishops on the same color
-        // or king and any number of bishops vs king and any number of bishops
-        // in the same color
-        if piece_count == bishops.len() + 2 && bishops.windows(2).all(|c| c[0] == c[1]) {
-            return true;
+        // the undo records cover the last moves of the history
+        if let Some(undo_stack) = &self.undo_stack {
+            let dropped = self.position_history.len() - 1 - ply;
+            let kept = undo_stack.len().saturating_sub(dropped);
+            board.undo_stack = Some(undo_stack[..kept].to_vec());
         }
 
-        false
+        Some(board)
     }
 
-    /// Returns true if the current position is a draw.
+    /// Returns the FEN piece placement of a single rank (e.g. "rnbqkbnr" or
+    /// "4P3"). Ranks go from 1 to 8, and the method will panic if the rank
+    /// provided is out of bounds.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let board = Board::from_fen("8/8/1k6/5K2/8/8/4N3/8 b - - 0 2").unwrap();
-    /// assert_eq!(board.draw(), true);
-    pub fn draw(&self) -> bool {
-        self.stalemate()
-            || self.insufficient_material()
-            || self.fifty_move_rule()
-            || self.threefold_repetition()
+    /// let board = Board::new();
+    /// assert_eq!(board.rank_fen(8), "rnbqkbnr");
+    /// assert_eq!(board.rank_fen(4), "8");
+    /// ```
+    pub fn rank_fen(&self, rank: usize) -> String {
+        assert!((1..=8).contains(&rank), "Rank must be between 1 and 8");
+
+        let mut fen = String::new();
+        fen::write_row_fen(&self.squares[8 - rank], &mut fen);
+        fen
     }
 
-    /// Makes a move on the board given its notation in [UCI](https://en.wikipedia.org/wiki/Universal_Chess_Interface)
-    /// protocol format notation. This method will accpedt either moves with
-    /// source and destination squares separated by a '-' or moves with source
-    /// and destination squares putted all together. Both "e2e4" and "e2-e4"
-    /// will be considered valid.
-    ///
-    /// If the move notation is invalid or the move is not legal, no move will
-    /// be applied. Also returns the move applied to the board.
+    /// Returns the color of the player who moves next.
     ///
     /// # Examples
     ///
     /// ```
-    /// use chessr::Board;
-    ///
-    /// let mut board = Board::new();
-    /// let r#move = board.make_uci_move("e2e4");
+    /// use chessr::{Board, Color};
     ///
-    /// assert!(r#move.is_some());
-    /// assert_eq!(
-    ///     board.fen(),
-    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
-    /// );
+    /// let board = Board::new();
+    /// assert_eq!(board.turn(), Color::White);
     /// ```
-    pub fn make_uci_move(&mut self, uci_str: &str) -> Option<Move> {
-        let r#move = Move::from_uci(uci_str, self);
-
-        if let Some(ref r#move) = r#move {
-            if self.legal_moves().contains(r#move) {
-                self.apply_move(r#move);
-            }
-        }
-
-        r#move
+    pub fn turn(&self) -> Color {
+        self.active_color
     }
 
-    /// Makes a move on the board given its [algebraic notation](https://www.chess.com/terms/chess-notation).
-    /// If the move notation is invalid or the move is not legal, no move will
-    /// be applied. Also returns the move that was applied.
+    /// Returns true if white moves next.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let mut board = Board::new();
-    /// let r#move = board.make_san_move("e4");
-    ///
-    /// assert!(r#move.is_some());
-    /// assert_eq!(
-    ///     board.fen(),
-    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
-    /// );
+    /// let board = Board::new();
+    /// assert_eq!(board.is_whites_turn(), true);
     /// ```
-    pub fn make_san_move(&mut self, algebraic_str: &str) -> Option<Move> {
-        let r#move = Move::from_san(algebraic_str, self);
-
-        if let Some(ref r#move) = r#move {
-            if self.legal_moves().contains(r#move) {
-                self.apply_move(r#move);
-            }
-        }
-
-        r#move
+    pub fn is_whites_turn(&self) -> bool {
+        self.active_color == Color::White
     }
 
-    /// Tries to make a move, accepting both standard and non-standard algebraic
-    /// notation. For making UCI moves or SAN moves see
-    /// [make_uci_move()](crate::Board::make_uci_move())
-    /// and [make_san_move()](crate::Board::make_san_move())
-    /// functions.
+    /// Returns a vector of all the pieces and their respective square
+    /// coordinates that are checking the king in the current position.
     ///
     /// # Examples
+    ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let mut board = Board::new();
+    /// let board = Board::from_fen("rnbqk1nr/ppp2ppp/4p3/3p4/1bPP4/5N2/PP2PPPP/RNBQKB1R w KQkq - 2 4").unwrap();
+    /// assert_eq!(board.checkers().len(), 1);
+    /// assert_eq!(board.checkers()[0].0.to_fen_char(), 'b');
+    /// assert_eq!(board.checkers()[0].1.to_string(), "b4");
+    pub fn checkers(&self) -> Vec<(Piece, SquareCoords)> {
+        self.square_attackers(self.king_square())
+    }
+
+    /// Returns the legal moves of the current position grouped by the way
+    /// they escape check: moving the king, capturing the checking piece or
+    /// blocking the check. Returns `None` if the king is not in check. When
+    /// there is more than one checking piece only king moves are possible.
     ///
-    /// // Standard algebraic notation.
-    /// let r#move = board.make_move("e4");
-    /// assert_eq!(r#move.is_some(), true);
+    /// # Examples
     ///
-    /// // Long algebraic notation without '-'.
-    /// let r#move = board.make_move("e7e5");
-    /// assert_eq!(r#move.is_some(), true);
+    /// ```
+    /// use chessr::Board;
     ///
-    /// // Long algebraic notation with '-'.
-    /// let r#move = board.make_move("f1-c4");
-    /// assert_eq!(r#move.is_some(), true);
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/6PP/r5K1 w - - 0 1").unwrap();
+    /// let escapes = board.check_escapes().unwrap();
+    ///
+    /// assert_eq!(escapes.king_moves.len(), 1);
+    /// assert!(escapes.captures_of_checker.is_empty());
+    /// assert!(escapes.blocks.is_empty());
     /// ```
-    pub fn make_move(&mut self, move_str: &str) -> Option<Move> {
-        // try to parse the move as UCI.
-        if let Some(r#move) = Move::from_uci(move_str, self) {
-            if self.legal_moves().contains(&r#move) {
-                self.apply_move(&r#move);
-                return Some(r#move);
-            }
+    pub fn check_escapes(&self) -> Option<CheckEscapes> {
+        let checkers = self.checkers();
+        if checkers.is_empty() {
+            return None;
         }
 
-        // try to parse the move as SAN.
-        if let Some(r#move) = Move::from_san(move_str, self) {
-            if self.legal_moves().contains(&r#move) {
-                self.apply_move(&r#move);
-                return Some(r#move);
+        let mut escapes = CheckEscapes {
+            king_moves: Vec::new(),
+            captures_of_checker: Vec::new(),
+            blocks: Vec::new(),
+        };
+
+        for r#move in self.legal_moves() {
+            let captures_checker = self
+                .capture_square(&r#move)
+                .is_some_and(|square| checkers.iter().any(|(_, s)| s == &square));
+
+            if r#move.piece == Some(Piece::King(self.active_color)) {
+                escapes.king_moves.push(r#move);
+            } else if captures_checker {
+                escapes.captures_of_checker.push(r#move);
+            } else {
+                escapes.blocks.push(r#move);
             }
         }
 
-        None
+        Some(escapes)
     }
 
-    /// Returns a vec of [Move] containing all possible legal moves in the
-    /// current position.
+    /// Returns true if there is a check in the current position.
     ///
     /// # Examples
     ///
     /// ```
     /// use chessr::Board;
     ///
-    /// let mut board = Board::new();
-    /// assert_eq!(board.legal_moves().len(), 20);
+    /// let board = Board::from_fen("rnbqk1nr/ppp2ppp/4p3/3p4/1bPP4/5N2/PP2PPPP/RNBQKB1R w KQkq - 2 4")
+    ///     .unwrap();
+    /// assert_eq!(board.check(), true);
     /// ```
-    pub fn legal_moves(&self) -> Vec<Move> {
-        movegen::generate_legal_moves(self)
+    pub fn check(&self) -> bool {
+        !self.checkers().is_empty()
     }
 
-    /// Returns the piece located at the given square, if any. If the square
-    /// provided is out of bounds, the method will panic.
-    pub(crate) fn get_piece(&self, square_coords: SquareCoords) -> Option<Piece> {
-        self.squares[square_coords.0][square_coords.1]
+    /// Returns true if the king of the given color is attacked, no matter
+    /// whose turn it is. Returns false if there is no king of that color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2r w - - 0 1").unwrap();
+    ///
+    /// assert!(board.is_king_attacked(Color::White));
+    /// assert!(!board.is_king_attacked(Color::Black));
+    /// ```
+    pub fn is_king_attacked(&self, color: Color) -> bool {
+        match self.find(Piece::King(color)).first() {
+            Some(&square) => !self.square_attackers_by(square, color.invert()).is_empty(),
+            None => false,
+        }
     }
 
-    /// Sets the piece at the given square. To remove a piece from a square,
-    /// pass `None` as the piece. If the square provided is out of bounds, the
-    /// method will panic.
-    pub(crate) fn set_piece(&mut self, square_coords: SquareCoords, piece: Option<Piece>) {
-        self.squares[square_coords.0][square_coords.1] = piece;
+    /// Returns true if the position is quiet: the side to move is not in check
+    /// and has no capture available, en passant included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.is_quiet(), true);
+    /// ```
+    pub fn is_quiet(&self) -> bool {
+        !self.check() && !self.legal_moves().iter().any(|r#move| r#move.capture)
     }
 
-    /// Applies a move on the board, updating the board state.
-    /// This method assumes that the move is legal and valid, otherwise
-    /// undefined behavior may occur.
-    pub(crate) fn apply_move(&mut self, r#move: &Move) {
-        // handle castling
-        if let Some(ref castle) = r#move.castle {
-            match castle {
-                CastleKind::Kingside => self.castle_kingside(),
-                CastleKind::Queenside => self.castle_queenside(),
-            }
+    /// Returns true if there is a checkmate in the current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board =
+    ///     Board::from_fen("rnb1kbnr/pppp1ppp/4p3/8/5PPq/8/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    /// assert_eq!(board.checkmate(), true);
+    /// ```
+    pub fn checkmate(&self) -> bool {
+        self.check() && self.legal_moves().is_empty()
+    }
+
+    /// Returns true if there is a stalemate in the current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("8/8/8/8/8/2k5/2p5/2K5 w - - 0 1").unwrap();
+    /// assert_eq!(board.stalemate(), true);
+    /// ```
+    pub fn stalemate(&self) -> bool {
+        !self.check() && self.legal_moves().is_empty()
+    }
+
+    /// Returns why the active player has no legal moves in the current
+    /// position, or `None` if there is at least one legal move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, NoMovesReason};
+    ///
+    /// let board = Board::from_fen("8/8/8/8/8/2k5/2p5/2K5 w - - 0 1").unwrap();
+    /// assert_eq!(board.no_moves_reason(), Some(NoMovesReason::Stalemate));
+    /// ```
+    pub fn no_moves_reason(&self) -> Option<NoMovesReason> {
+        if !self.legal_moves().is_empty() {
+            return None;
         }
 
-        // handle normal move and en passant
-        if let (Some(src_square), Some(dst_square)) = (r#move.src_square, r#move.dst_square) {
-            // handle en pasant capture
-            if self.en_passant_target.is_some_and(|s| s == dst_square) {
-                let en_passant_square = self.en_passant_target.unwrap();
+        match self.check() {
+            true => Some(NoMovesReason::Checkmate),
+            false => Some(NoMovesReason::Stalemate),
+        }
+    }
 
-                // calculate the square in which the en passant target is located
-                let en_passant_capture_square = match self.active_color {
-                    Color::White => (en_passant_square.0 + 1, en_passant_square.1).into(),
-                    Color::Black => (en_passant_square.0 - 1, en_passant_square.1).into(),
-                };
+    /// Returns how the game has ended, or `None` if it is still going on. Only
+    /// checkmate and the draws that end the game automatically are taken into
+    /// account; draws that have to be claimed by a player don't end the game.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color, Outcome};
+    ///
+    /// let board =
+    ///     Board::from_fen("rnb1kbnr/pppp1ppp/4p3/8/5PPq/8/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    /// assert_eq!(board.outcome(), Some(Outcome::Checkmate(Color::Black)));
+    /// ```
+    pub fn outcome(&self) -> Option<Outcome> {
+        if let Some(reason) = self.no_moves_reason() {
+            return match reason {
+                NoMovesReason::Checkmate => Some(Outcome::Checkmate(self.active_color.invert())),
+                NoMovesReason::Stalemate => Some(Outcome::Stalemate),
+            };
+        }
+
+        if self.insufficient_material() {
+            Some(Outcome::InsufficientMaterial)
+        } else if self.seventyfive_move_rule() {
+            Some(Outcome::SeventyFiveMoveRule)
+        } else if self.fivefold_repetition() {
+            Some(Outcome::FivefoldRepetition)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the game has ended, see
+    /// [outcome()](crate::Board::outcome()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.is_game_over(), false);
+    /// ```
+    pub fn is_game_over(&self) -> bool {
+        self.outcome().is_some()
+    }
+
+    /// Returns true if the king of the given color has moved. A king that
+    /// still has castle rights can't have moved. Otherwise, this relies on a
+    /// flag set whenever the king moves, since both castle rights are also
+    /// lost when both rooks move. When the board is created from a FEN, a
+    /// king that is not on its starting square is considered to have moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color};
+    ///
+    /// let mut board = Board::new();
+    /// assert_eq!(board.king_has_moved(Color::White), false);
+    ///
+    /// for r#move in ["e4", "e5", "Ke2"] {
+    ///     board.make_move(r#move);
+    /// }
+    /// assert_eq!(board.king_has_moved(Color::White), true);
+    /// ```
+    pub fn king_has_moved(&self, color: Color) -> bool {
+        if self.castle_rights.iter().any(|r| r.color() == color) {
+            return false;
+        }
+
+        match color {
+            Color::White => self.white_king_moved,
+            Color::Black => self.black_king_moved,
+        }
+    }
+
+    /// Returns the first move of a forced checkmate in at most `n` moves for
+    /// the active player, if there is one. This performs an exhaustive
+    /// depth-limited search, so it is only meant for small values of `n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+    /// let r#move = board.mate_in(1).unwrap();
+    /// assert_eq!(board.san_of(&r#move), "Rh8#");
+    /// ```
+    pub fn mate_in(&self, n: u32) -> Option<Move> {
+        if n == 0 {
+            return None;
+        }
+
+        for r#move in self.legal_moves() {
+            let mut board = self.for_search();
+            board.apply_move(&r#move);
+
+            let replies = board.legal_moves();
+            if replies.is_empty() {
+                // a stalemate doesn't count
+                if board.check() {
+                    return Some(r#move);
+                }
 
-                self.set_piece(en_passant_capture_square, None);
+                continue;
             }
 
-            // reset halfmove clock if a pawn is moved or a piece is captured
-            if r#move.piece == Some(Piece::Pawn(self.active_color)) || r#move.capture {
-                self.halfmove_clock = 0;
-            } else {
-                self.halfmove_clock += 1;
+            // every reply of the opponent must still allow a mate
+            let forced_mate = n > 1
+                && replies.iter().all(|reply| {
+                    let mut board = board.clone();
+                    board.apply_move(reply);
+                    board.mate_in(n - 1).is_some()
+                });
+
+            if forced_mate {
+                return Some(r#move);
+            }
+        }
+
+        None
+    }
+
+    /// Returns true if 50 moves have been made by each player without a pawn
+    /// move or a capture, that is, 100 half-moves as counted by the halfmove
+    /// clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.fifty_move_rule(), false);
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+    /// assert_eq!(board.fifty_move_rule(), true);
+    /// ```
+    pub fn fifty_move_rule(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// Returns true if the current position is a draw by threefold repetition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    ///
+    /// for r#move in &[
+    ///     "e4", "e5", "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8",
+    /// ] {
+    ///     board.make_move(r#move);
+    /// }
+    ///
+    /// assert_eq!(board.threefold_repetition(), true);
+    /// ```
+    pub fn threefold_repetition(&self) -> bool {
+        self.max_repetitions() >= 3
+    }
+
+    /// Returns how many times the current position has occurred in the game,
+    /// including the current occurrence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// assert_eq!(board.repetition_count(), 1);
+    ///
+    /// for r#move in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+    ///     board.make_move(r#move);
+    /// }
+    /// assert_eq!(board.repetition_count(), 2);
+    /// ```
+    pub fn repetition_count(&self) -> usize {
+        let fen = self.fen();
+        let key = position_key(&fen);
+
+        self.position_history
+            .iter()
+            .filter(|pos| position_key(pos) == key)
+            .count()
+    }
+
+    /// Returns true if both boards hold the same position, no matter the move
+    /// order that led to it: piece placement, active color, castle rights
+    /// and en passant target are compared, while the move counters and the
+    /// history are not. An en passant target no pawn can capture on is
+    /// ignored. This is the same comparison made by `==`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut first = Board::new();
+    /// let mut second = Board::new();
+    ///
+    /// for r#move in ["Nf3", "Nf6", "Nc3"] {
+    ///     first.make_move(r#move);
+    /// }
+    /// for r#move in ["Nc3", "Nf6", "Nf3"] {
+    ///     second.make_move(r#move);
+    /// }
+    ///
+    /// assert!(first.positions_equal_ignoring_counters(&second));
+    /// ```
+    pub fn positions_equal_ignoring_counters(&self, other: &Board) -> bool {
+        self.position_identity() == other.position_identity()
+    }
+
+    /// Returns true if 75 moves have been made without a pawn move or a
+    /// capture. Unlike the fifty-move rule, the game is drawn automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 120").unwrap();
+    /// assert_eq!(board.seventyfive_move_rule(), true);
+    /// ```
+    pub fn seventyfive_move_rule(&self) -> bool {
+        self.halfmove_clock >= 150
+    }
+
+    /// Returns true if a position has occurred at least five times. Unlike
+    /// threefold repetition, the game is drawn automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.fivefold_repetition(), false);
+    /// ```
+    pub fn fivefold_repetition(&self) -> bool {
+        self.max_repetitions() >= 5
+    }
+
+    /// Returns true if the current position is a draw by insufficient material.
+    /// This is one kind of dead position, see
+    /// [is_dead_position()](crate::Board::is_dead_position()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("2k5/4b3/8/8/8/8/8/2K1B1B1 w - - 0 1").unwrap();
+    /// assert_eq!(board.insufficient_material(), true);
+    /// ```
+    pub fn insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut bishop_square_colors = Vec::new();
+
+        for (square, piece) in self.pieces() {
+            match piece {
+                Piece::King(_) => (),
+                Piece::Knight(_) => knights += 1,
+                // what matters for bishops is the color of the square they are
+                // on, not which side they belong to
+                Piece::Bishop(_) => bishop_square_colors.push(match (square.0 + square.1) % 2 {
+                    0 => Color::White,
+                    _ => Color::Black,
+                }),
+                // any pawn, rook or queen is enough material to mate
+                _ => return false,
             }
+        }
+
+        let minor_pieces = knights + bishop_square_colors.len();
+
+        // king vs king, king and bishop vs king or king and knight vs king
+        if minor_pieces <= 1 {
+            return true;
+        }
+
+        // kings and any number of bishops of either side, all of them on
+        // squares of the same color
+        knights == 0 && bishop_square_colors.windows(2).all(|c| c[0] == c[1])
+    }
+
+    /// Returns true if the position is dead: no sequence of legal moves can
+    /// lead to a checkmate, so the game is drawn automatically under FIDE
+    /// rules. This is the entry point for every kind of dead position, while
+    /// [insufficient_material()](crate::Board::insufficient_material()) only
+    /// answers for the material on the board.
+    ///
+    /// For now the dead positions detected are the ones without enough
+    /// material to mate, which includes kings with any number of bishops of
+    /// either side as long as all of them stand on squares of the same color.
+    /// Blocked pawn structures and other dead positions that still have mating
+    /// material are not detected yet, and will be reported here once they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// // all the bishops are on light squares
+    /// let board = Board::from_fen("4k3/8/8/8/2b5/8/8/1B1K1B2 w - - 0 1").unwrap();
+    /// assert!(board.is_dead_position());
+    /// ```
+    pub fn is_dead_position(&self) -> bool {
+        self.insufficient_material()
+    }
+
+    /// Returns true if the current position is a draw.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("8/8/1k6/5K2/8/8/4N3/8 b - - 0 2").unwrap();
+    /// assert_eq!(board.draw(), true);
+    pub fn draw(&self) -> bool {
+        self.stalemate()
+            || self.insufficient_material()
+            || self.fifty_move_rule()
+            || self.threefold_repetition()
+    }
+
+    /// Returns true if the current position is a draw that ends the game
+    /// without any player having to claim it: stalemate, insufficient
+    /// material, the seventy-five-move rule or fivefold repetition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+    /// assert_eq!(board.draw(), true);
+    /// assert_eq!(board.is_automatic_draw(), false);
+    /// ```
+    pub fn is_automatic_draw(&self) -> bool {
+        self.stalemate()
+            || self.insufficient_material()
+            || self.seventyfive_move_rule()
+            || self.fivefold_repetition()
+    }
+
+    /// Returns the draw the player to move can claim in the current position,
+    /// either by threefold repetition or by the fifty move rule, so a server
+    /// can end the game on its behalf. Returns `None` if no draw can be
+    /// claimed or if the game has already ended, see
+    /// [outcome()](crate::Board::outcome()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Outcome};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+    /// assert_eq!(board.auto_draw_claim(), Some(Outcome::FiftyMoveRule));
+    /// assert_eq!(Board::new().auto_draw_claim(), None);
+    /// ```
+    pub fn auto_draw_claim(&self) -> Option<Outcome> {
+        if self.outcome().is_some() {
+            return None;
+        }
+
+        if self.threefold_repetition() {
+            Some(Outcome::ThreefoldRepetition)
+        } else if self.fifty_move_rule() {
+            Some(Outcome::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the legal moves that don't lead to a position where a draw can
+    /// be claimed, either by threefold repetition or by the fifty move rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    ///
+    /// for r#move in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+    ///     board.make_move(r#move);
+    /// }
+    ///
+    /// // Ng8 would repeat the starting position for the third time
+    /// assert_eq!(board.moves_avoiding_draw().len(), board.legal_moves().len() - 1);
+    /// ```
+    pub fn moves_avoiding_draw(&self) -> Vec<Move> {
+        self.legal_moves()
+            .into_iter()
+            .filter(|r#move| {
+                let mut board = self.clone();
+                board.apply_move(r#move);
+                !board.threefold_repetition() && !board.fifty_move_rule()
+            })
+            .collect()
+    }
+
+    /// Makes a move on the board given its notation in [UCI](https://en.wikipedia.org/wiki/Universal_Chess_Interface)
+    /// protocol format notation. This method will accpedt either moves with
+    /// source and destination squares separated by a '-' or moves with source
+    /// and destination squares putted all together. Both "e2e4" and "e2-e4"
+    /// will be considered valid.
+    ///
+    /// If the move notation is invalid or the move is not legal, no move will
+    /// be applied. Also returns the move applied to the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// let r#move = board.make_uci_move("e2e4");
+    ///
+    /// assert!(r#move.is_some());
+    /// assert_eq!(
+    ///     board.fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+    /// );
+    /// ```
+    pub fn make_uci_move(&mut self, uci_str: &str) -> Option<Move> {
+        let r#move = Move::from_uci(uci_str, self);
+
+        if let Some(ref r#move) = r#move {
+            if self.legal_moves().contains(r#move) {
+                self.apply_move(r#move);
+            }
+        }
+
+        r#move
+    }
+
+    /// Makes a move on the board given its [UCI notation](https://en.wikipedia.org/wiki/Universal_Chess_Interface)
+    /// and returns the FEN string of the resulting position. If the move
+    /// notation is invalid or the move is not legal, the board is left
+    /// unchanged and `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    ///
+    /// assert_eq!(
+    ///     board.push_uci("e2e4").as_deref(),
+    ///     Some("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+    /// );
+    /// assert_eq!(board.push_uci("e2e4"), None);
+    /// ```
+    pub fn push_uci(&mut self, uci_str: &str) -> Option<String> {
+        let r#move = Move::from_uci(uci_str, self)?;
+
+        if !self.push(&r#move) {
+            return None;
+        }
+
+        Some(self.fen())
+    }
+
+    /// Makes a move on the board given its [algebraic notation](https://www.chess.com/terms/chess-notation).
+    /// If the move notation is invalid or the move is not legal, no move will
+    /// be applied. Also returns the move that was applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// let r#move = board.make_san_move("e4");
+    ///
+    /// assert!(r#move.is_some());
+    /// assert_eq!(
+    ///     board.fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+    /// );
+    /// ```
+    pub fn make_san_move(&mut self, algebraic_str: &str) -> Option<Move> {
+        let r#move = Move::from_san(algebraic_str, self);
+
+        if let Some(ref r#move) = r#move {
+            if self.legal_moves().contains(r#move) {
+                self.apply_move(r#move);
+            }
+        }
+
+        r#move
+    }
+
+    /// Same as [make_san_move()](crate::Board::make_san_move()), but the piece
+    /// letters of the move are read in the given [SanLanguage] (e.g. "Sf3" in
+    /// German or "Cf3" in French for "Nf3"). Returns `None` without making a
+    /// move if the move uses a piece letter the language doesn't have, see
+    /// [SanLanguage::translate()](crate::SanLanguage::translate()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SanLanguage};
+    ///
+    /// let mut board = Board::new();
+    /// let r#move = board.make_san_move_localized("Sf3", SanLanguage::German);
+    ///
+    /// assert_eq!(r#move.map(|m| m.to_uci_str()), Some("g1f3".to_string()));
+    /// ```
+    pub fn make_san_move_localized(&mut self, san: &str, language: SanLanguage) -> Option<Move> {
+        self.make_san_move(&language.translate(san)?)
+    }
+
+    /// Tries to make a move, accepting both standard and non-standard algebraic
+    /// notation. For making UCI moves or SAN moves see
+    /// [make_uci_move()](crate::Board::make_uci_move())
+    /// and [make_san_move()](crate::Board::make_san_move())
+    /// functions.
+    ///
+    /// # Examples
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    ///
+    /// // Standard algebraic notation.
+    /// let r#move = board.make_move("e4");
+    /// assert_eq!(r#move.is_some(), true);
+    ///
+    /// // Long algebraic notation without '-'.
+    /// let r#move = board.make_move("e7e5");
+    /// assert_eq!(r#move.is_some(), true);
+    ///
+    /// // Long algebraic notation with '-'.
+    /// let r#move = board.make_move("f1-c4");
+    /// assert_eq!(r#move.is_some(), true);
+    /// ```
+    pub fn make_move(&mut self, move_str: &str) -> Option<Move> {
+        let r#move = self.parse_legal_move(move_str)?;
+        self.apply_move(&r#move);
+        Some(r#move)
+    }
+
+    /// Same as [make_move()](crate::Board::make_move()), but refuses to play
+    /// once the game is over and tells why the move was not made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, PlayError};
+    ///
+    /// let mut board = Board::new();
+    ///
+    /// assert!(board.play("e4").is_ok());
+    /// assert!(matches!(board.play("e7e4"), Err(PlayError::IllegalMove)));
+    /// assert!(matches!(board.play("xyz"), Err(PlayError::InvalidMove)));
+    /// ```
+    pub fn play(&mut self, move_str: &str) -> Result<Move, PlayError> {
+        if let Some(outcome) = self.outcome() {
+            return Err(PlayError::GameOver(outcome));
+        }
+
+        if let Some(r#move) = self.make_move(move_str) {
+            return Ok(r#move);
+        }
+
+        match Move::from_uci(move_str, self).or_else(|| Move::from_san(move_str, self)) {
+            Some(_) => Err(PlayError::IllegalMove),
+            None => Err(PlayError::InvalidMove),
+        }
+    }
+
+    /// Plays a game from the starting position choosing every move at random
+    /// among the legal ones, until the game is over. Returns the final board,
+    /// the moves played and the [Outcome] of the game. The game always ends,
+    /// at the latest by the seventy-five-move rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let (board, moves, outcome) = Board::play_random_game(&mut rand::thread_rng());
+    ///
+    /// assert_eq!(board.outcome(), Some(outcome));
+    /// assert_eq!(board.position_history.len(), moves.len() + 1);
+    /// ```
+    pub fn play_random_game<R: Rng>(rng: &mut R) -> (Board, Vec<Move>, Outcome) {
+        let mut board = Board::new();
+        let mut moves = Vec::new();
+
+        loop {
+            if let Some(outcome) = board.outcome() {
+                return (board, moves, outcome);
+            }
+
+            let legal_moves = board.legal_moves();
+            let r#move = legal_moves[rng.gen_range(0..legal_moves.len())];
+
+            board.apply_move(&r#move);
+            moves.push(r#move);
+        }
+    }
+
+    /// Same as [make_move()](crate::Board::make_move()), but also returns the
+    /// piece captured by the move, if any. For en passant captures this is
+    /// the pawn taken from beside the destination square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color, Piece};
+    ///
+    /// let mut board =
+    ///     Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+    ///
+    /// let (_, captured) = board.make_move_capturing("exd5").unwrap();
+    /// assert_eq!(captured, Some(Piece::Pawn(Color::Black)));
+    /// ```
+    pub fn make_move_capturing(&mut self, move_str: &str) -> Option<(Move, Option<Piece>)> {
+        let r#move = self.parse_legal_move(move_str)?;
+        let captured = self
+            .capture_square(&r#move)
+            .and_then(|square| self.get_piece(square));
+
+        self.apply_move(&r#move);
+        Some((r#move, captured))
+    }
+
+    /// Same as [make_move()](crate::Board::make_move()), but a UCI pawn move
+    /// to the last rank without a promotion piece (e.g. "e7e8") promotes to
+    /// a queen instead of being rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// let r#move = board.make_move_default_promotion("b7b8");
+    ///
+    /// assert!(r#move.is_some());
+    /// assert_eq!(board.fen(), "1Q2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    /// ```
+    pub fn make_move_default_promotion(&mut self, move_str: &str) -> Option<Move> {
+        if let Some(mut r#move) = Move::from_uci(move_str, self) {
+            let last_row = match self.active_color {
+                Color::White => 0,
+                Color::Black => 7,
+            };
+
+            if r#move.promotion.is_none()
+                && r#move.piece == Some(Piece::Pawn(self.active_color))
+                && r#move.dst_square.is_some_and(|s| s.0 == last_row)
+            {
+                r#move.promotion = Some(Piece::Queen(self.active_color));
+
+                if self.push(&r#move) {
+                    return Some(r#move);
+                }
+            }
+        }
+
+        self.make_move(move_str)
+    }
+
+    /// Moves the piece on the `from` square to the `to` square if that is a
+    /// legal move in the current position, without going through any move
+    /// notation. Castling is done by moving the king two squares. When a pawn
+    /// reaches the last rank the `promotion` piece must be given; its color is
+    /// ignored and the active color is used instead. Returns the move made, or
+    /// `None` if there is no such legal move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let mut board = Board::new();
+    /// let from = SquareCoords::from_san_str("e2").unwrap();
+    /// let to = SquareCoords::from_san_str("e4").unwrap();
+    ///
+    /// assert!(board.move_piece(from, to, None).is_some());
+    /// assert!(board.move_piece(from, to, None).is_none());
+    /// ```
+    pub fn move_piece(
+        &mut self,
+        from: SquareCoords,
+        to: SquareCoords,
+        promotion: Option<Piece>,
+    ) -> Option<Move> {
+        let promotion =
+            promotion.and_then(|p| Piece::from_uci_char(p.to_uci_char(), self.active_color));
+
+        let r#move = self
+            .legal_moves()
+            .into_iter()
+            .find(|m| m.squares() == (from, to) && m.promotion == promotion)?;
+
+        self.apply_move(&r#move);
+        Some(r#move)
+    }
+
+    /// Makes the given move on the board if it is legal in the current
+    /// position. Returns true if the move was applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// let r#move = board.legal_moves()[0];
+    ///
+    /// assert_eq!(board.push(&r#move), true);
+    /// assert_eq!(board.push(&r#move), false);
+    /// ```
+    pub fn push(&mut self, r#move: &Move) -> bool {
+        if !self.legal_moves().contains(r#move) {
+            return false;
+        }
+
+        self.apply_move(r#move);
+        true
+    }
+
+    /// Returns true if the given move in [algebraic notation](https://www.chess.com/terms/chess-notation)
+    /// is legal in the current position, without making it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert!(board.is_legal_san("Nf3"));
+    /// assert!(!board.is_legal_san("Nf4"));
+    /// ```
+    pub fn is_legal_san(&self, san: &str) -> bool {
+        Move::from_san(san, self).is_some_and(|r#move| self.legal_moves().contains(&r#move))
+    }
+
+    /// Returns true if the given move in [UCI notation](https://en.wikipedia.org/wiki/Universal_Chess_Interface)
+    /// is legal in the current position, without making it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert!(board.is_legal_uci("g1f3"));
+    /// assert!(!board.is_legal_uci("e2e5"));
+    /// ```
+    pub fn is_legal_uci(&self, uci: &str) -> bool {
+        Move::from_uci(uci, self).is_some_and(|r#move| self.legal_moves().contains(&r#move))
+    }
+
+    /// Makes the given move on the board without checking that it is legal,
+    /// which saves generating the legal moves again when the move already
+    /// comes from [legal_moves()](crate::Board::legal_moves()), e.g. inside a
+    /// search. The move **must** be legal in the current position: applying
+    /// an illegal move leaves the board in an inconsistent state, and any
+    /// result obtained from it afterwards is meaningless. Use
+    /// [push()](crate::Board::push()) when in doubt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    ///
+    /// for r#move in board.legal_moves() {
+    ///     let mut board = board.clone();
+    ///     board.apply_unchecked(&r#move);
+    ///     assert_eq!(board.legal_moves().len(), 20);
+    /// }
+    /// ```
+    pub fn apply_unchecked(&mut self, r#move: &Move) {
+        self.apply_move(r#move);
+    }
+
+    /// Starts recording what each move made on the board changes, so moves can
+    /// be taken back with [undo()](crate::Board::undo()). Recording is off by
+    /// default: most boards, like the copies made to try out a move, never
+    /// take one back, and the records would grow for as long as the board
+    /// lives. Moves made before calling this can't be taken back, and calling
+    /// it again keeps the moves already recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// board.make_move("e4");
+    /// assert!(!board.undo());
+    ///
+    /// board.enable_undo();
+    /// board.make_move("e5");
+    /// assert!(board.undo());
+    /// assert!(!board.undo());
+    /// ```
+    pub fn enable_undo(&mut self) {
+        self.undo_stack.get_or_insert_with(Vec::new);
+    }
+
+    /// Takes back the last move made on the board, restoring the position
+    /// before it. Moves can be taken back one after another down to the
+    /// position where [enable_undo()](crate::Board::enable_undo()) was called.
+    /// Returns false if there is no move to take back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// board.enable_undo();
+    /// board.make_move("e4");
+    /// board.make_move("e5");
+    ///
+    /// assert!(board.undo());
+    /// assert!(board.undo());
+    /// assert_eq!(board.fen(), Board::new().fen());
+    /// assert!(!board.undo());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        let undo = match self.undo_stack.as_mut().and_then(Vec::pop) {
+            Some(undo) => undo,
+            None => return false,
+        };
+        let r#move = undo.r#move;
+
+        self.active_color = r#move.color;
+
+        match r#move.castle {
+            Some(castle) => self.undo_castle(castle),
+            None => {
+                let (src_square, dst_square) = r#move.squares();
+
+                // the piece goes back unpromoted, since it is the one that moved
+                self.set_piece(dst_square, None);
+                self.set_piece(src_square, r#move.piece);
+
+                if let Some((square, piece)) = undo.captured {
+                    self.set_piece(square, Some(piece));
+                }
+            }
+        }
+
+        if let Some(castle_rights) = undo.castle_rights {
+            self.castle_rights = castle_rights;
+        }
+
+        self.en_passant_target = undo.en_passant_target;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.white_king_moved = undo.white_king_moved;
+        self.black_king_moved = undo.black_king_moved;
+
+        if r#move.color == Color::Black {
+            self.fullmove_number -= 1;
+        }
+
+        // the starting position always stays in the history
+        if self.position_history.len() > 1 {
+            self.position_history.pop();
+        }
+        self.legal_moves_cache.clear();
+
+        true
+    }
+
+    /// Sets or clears the en passant target square, given in algebraic
+    /// notation (e.g. "d6"). A target on the 6th rank requires white to move
+    /// and a black pawn right below it, and a target on the 3rd rank requires
+    /// black to move and a white pawn right above it. The target square and
+    /// the square the pawn came from must be empty. Otherwise an error is
+    /// returned and the board is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 2").unwrap();
+    ///
+    /// assert!(board.set_en_passant(Some("d6")).is_ok());
+    /// assert_eq!(board.fen(), "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2");
+    ///
+    /// assert!(board.set_en_passant(Some("e6")).is_err());
+    /// assert!(board.set_en_passant(None).is_ok());
+    /// assert_eq!(board.en_passant_target, None);
+    /// ```
+    pub fn set_en_passant(&mut self, target: Option<&str>) -> Result<(), FenParseError> {
+        let target = match target {
+            Some(target) => {
+                let square = SquareCoords::from_san_str(target).ok_or(FenParseError::EnPassant)?;
+
+                if !self.valid_en_passant_target(square) {
+                    return Err(FenParseError::EnPassant);
+                }
+
+                Some(square)
+            }
+            None => None,
+        };
+
+        self.en_passant_target = target;
+
+        // keep the last entry of the history in sync with the current position
+        let fen = self.fen();
+        if let Some(last) = self.position_history.last_mut() {
+            *last = fen;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a vec of [Move] containing all possible legal moves in the
+    /// current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// assert_eq!(board.legal_moves().len(), 20);
+    /// ```
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let identity = self.position_identity();
+
+        if let Some(moves) = self.legal_moves_cache.get(&identity) {
+            return moves;
+        }
+
+        let moves = movegen::generate_legal_moves(self);
+        self.legal_moves_cache.set(identity, moves.clone());
+        moves
+    }
+
+    /// Returns the same moves as [legal_moves()](crate::Board::legal_moves())
+    /// sorted in a stable order: by source square, then destination square,
+    /// then promotion piece (queen, rook, bishop, knight). Squares are ordered
+    /// from a1 to h8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// let legal_moves = board.legal_moves_sorted();
+    /// assert_eq!(legal_moves[0].to_uci_str(), "b1a3");
+    /// ```
+    pub fn legal_moves_sorted(&self) -> Vec<Move> {
+        let mut legal_moves = self.legal_moves();
+        legal_moves.sort_by_key(Move::sort_key);
+        legal_moves
+    }
+
+    /// Returns the same moves as [legal_moves()](crate::Board::legal_moves())
+    /// ordered for searching or listing: captures first, the most valuable
+    /// victims first and, for the same victim, the least valuable attackers
+    /// first (MVV-LVA); then promotions, the queen first; then the rest of the
+    /// moves. Promotions that capture are ordered with the captures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/1P6/8/3q4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    /// let ordered = board.legal_moves_ordered();
+    ///
+    /// assert_eq!(ordered[0].to_uci_str(), "e4d5");
+    /// assert_eq!(ordered[1].to_uci_str(), "b7b8q");
+    /// ```
+    pub fn legal_moves_ordered(&self) -> Vec<Move> {
+        let mut legal_moves = self.legal_moves();
+
+        legal_moves.sort_by_key(|r#move| {
+            let attacker = match r#move.piece {
+                // the king can only capture undefended pieces, try it last
+                Some(Piece::King(_)) => i32::MAX,
+                Some(piece) => piece.value(),
+                None => 0,
+            };
+            let promotion = r#move.promotion.map_or(0, |p| p.value());
+
+            match self.capture_square(r#move) {
+                Some(square) => {
+                    let victim = self.get_piece(square).map_or(0, |p| p.value());
+                    (0, -victim, attacker, -promotion)
+                }
+                None if r#move.promotion.is_some() => (1, 0, 0, -promotion),
+                None => (2, 0, 0, 0),
+            }
+        });
+
+        legal_moves
+    }
+
+    /// Returns the index of the given move in
+    /// [legal_moves_sorted()](crate::Board::legal_moves_sorted()), or `None` if
+    /// the move is not legal in the current position. Together with
+    /// [move_by_index()](crate::Board::move_by_index()) this gives a stable
+    /// integer encoding of the moves of a position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board = Board::new();
+    /// let r#move = Move::from_san("Na3", &board).unwrap();
+    ///
+    /// assert_eq!(board.move_index(&r#move), Some(0));
+    /// ```
+    pub fn move_index(&self, r#move: &Move) -> Option<usize> {
+        self.legal_moves_sorted().iter().position(|m| m == r#move)
+    }
+
+    /// Returns the move at the given index of
+    /// [legal_moves_sorted()](crate::Board::legal_moves_sorted()), or `None` if
+    /// the index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert_eq!(board.move_by_index(0).unwrap().to_uci_str(), "b1a3");
+    /// assert_eq!(board.move_by_index(20), None);
+    /// ```
+    pub fn move_by_index(&self, index: usize) -> Option<Move> {
+        self.legal_moves_sorted().get(index).copied()
+    }
+
+    /// Returns the square of the piece removed from the board by the given
+    /// move. This is the destination square for normal captures and the
+    /// square of the captured pawn for en passant captures. Returns `None` if
+    /// the move is not a capture.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board =
+    ///     Board::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3").unwrap();
+    /// let r#move = Move::from_san("exd6", &board).unwrap();
+    /// assert_eq!(board.capture_square(&r#move).unwrap().to_string(), "d5");
+    /// ```
+    pub fn capture_square(&self, r#move: &Move) -> Option<SquareCoords> {
+        let dst_square = r#move.dst_square?;
+
+        if r#move.piece == Some(Piece::Pawn(self.active_color))
+            && self.en_passant_target == Some(dst_square)
+        {
+            // the captured pawn is located right behind the en passant target
+            return match self.active_color {
+                Color::White => Some((dst_square.0 + 1, dst_square.1).into()),
+                Color::Black => Some((dst_square.0 - 1, dst_square.1).into()),
+            };
+        }
+
+        self.get_piece(dst_square).map(|_| dst_square)
+    }
+
+    /// Returns the `(row, column)` offset from the source to the destination
+    /// square of the given move, with rows counted from the 8th rank down as
+    /// in [SquareCoords], so a white pawn push gives a negative row offset.
+    /// Returns `None` for castles, which move two pieces. Renderers can use it
+    /// to draw straight arrows, or bent ones for knight moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board = Board::new();
+    ///
+    /// let r#move = Move::from_san("Nf3", &board).unwrap();
+    /// assert_eq!(board.move_vector(&r#move), Some((-2, -1)));
+    ///
+    /// let r#move = Move::from_san("e4", &board).unwrap();
+    /// assert_eq!(board.move_vector(&r#move), Some((-2, 0)));
+    /// ```
+    pub fn move_vector(&self, r#move: &Move) -> Option<(i8, i8)> {
+        if r#move.castle.is_some() {
+            return None;
+        }
+
+        let src_square = r#move.src_square?;
+        let dst_square = r#move.dst_square?;
+
+        Some((
+            dst_square.0 as i8 - src_square.0 as i8,
+            dst_square.1 as i8 - src_square.1 as i8,
+        ))
+    }
+
+    /// Returns the legal moves in the current position made by pieces of the
+    /// given kind. Castles are made by the king.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, PieceKind};
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.legal_moves_of_kind(PieceKind::Knight).len(), 4);
+    /// ```
+    pub fn legal_moves_of_kind(&self, kind: PieceKind) -> Vec<Move> {
+        self.legal_moves()
+            .into_iter()
+            .filter(|r#move| match r#move.piece {
+                Some(piece) => piece.kind() == kind,
+                None => kind == PieceKind::King,
+            })
+            .collect()
+    }
+
+    /// Returns the legal moves in the current position grouped by the square
+    /// of the piece that moves. Castles are grouped under the king's square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let board = Board::new();
+    /// let grouped_moves = board.legal_moves_grouped();
+    ///
+    /// let g1 = SquareCoords::from_san_str("g1").unwrap();
+    /// assert_eq!(grouped_moves[&g1].len(), 2);
+    /// ```
+    pub fn legal_moves_grouped(&self) -> HashMap<SquareCoords, Vec<Move>> {
+        let mut grouped_moves: HashMap<SquareCoords, Vec<Move>> = HashMap::new();
+
+        for r#move in self.legal_moves() {
+            let src_square = match r#move.src_square {
+                Some(src_square) => src_square,
+                None => self.king_square(),
+            };
+
+            grouped_moves.entry(src_square).or_default().push(r#move);
+        }
+
+        grouped_moves
+    }
+
+    /// Returns the squares the piece located at the given square can legally
+    /// move to. For the king, this includes the squares it lands on when
+    /// castling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let board = Board::new();
+    /// let destinations = board.legal_destinations(SquareCoords::from_san_str("g1").unwrap());
+    ///
+    /// assert_eq!(destinations.len(), 2);
+    /// assert!(destinations.contains(&SquareCoords::from_san_str("f3").unwrap()));
+    /// ```
+    pub fn legal_destinations(&self, from: SquareCoords) -> Vec<SquareCoords> {
+        let mut destinations = Vec::new();
+
+        for r#move in self.legal_moves() {
+            let (src_square, dst_square) = r#move.squares();
+
+            // promotions share the same destination square
+            if src_square == from && !destinations.contains(&dst_square) {
+                destinations.push(dst_square);
+            }
+        }
+
+        destinations
+    }
+
+    /// Returns true if a pawn of the active color stands on `from` and `to`
+    /// is on the last rank for it, so moving between them would promote. This
+    /// lets a UI ask for the promotion piece before the move is made. Whether
+    /// the move is legal is not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let board = Board::from_fen("4k3/P7/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    /// let square = |s| SquareCoords::from_san_str(s).unwrap();
+    ///
+    /// assert!(board.is_promotion(square("a7"), square("a8")));
+    /// assert!(!board.is_promotion(square("e2"), square("e4")));
+    /// ```
+    pub fn is_promotion(&self, from: SquareCoords, to: SquareCoords) -> bool {
+        let promotion_row = match self.active_color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+
+        self.get_piece(from) == Some(Piece::Pawn(self.active_color)) && to.0 == promotion_row
+    }
+
+    /// Returns true if any legal move in the current position lands on the
+    /// given square, which for castles is the square the king moves to. This
+    /// stops at the first such move instead of generating every legal move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert!(board.can_reach(SquareCoords::from_san_str("e4").unwrap()));
+    /// assert!(!board.can_reach(SquareCoords::from_san_str("e5").unwrap()));
+    /// ```
+    pub fn can_reach(&self, dst_square: SquareCoords) -> bool {
+        movegen::can_reach(self, dst_square)
+    }
+
+    /// Returns which squares are attacked by the pieces of the given color,
+    /// indexed like [squares](crate::Board::squares), computed in a single
+    /// sweep over the board. An occupied square counts as attacked when a
+    /// piece of the given color could capture on it, if it was an opponent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color};
+    ///
+    /// let board = Board::new();
+    /// let attacked = board.attacked_squares(Color::White);
+    ///
+    /// // the 3rd rank is covered by the pawns, e4 is not attacked
+    /// assert!(attacked[5].iter().all(|&a| a));
+    /// assert!(!attacked[4][4]);
+    /// ```
+    pub fn attacked_squares(&self, by: Color) -> [[bool; 8]; 8] {
+        movegen::attacked_squares(self, by)
+    }
+
+    /// Returns the squares attacked by the pieces of the given color, ordered
+    /// from a1 to h8. Like [attacked_squares()](crate::Board::attacked_squares()),
+    /// this includes squares of pieces of the same color the attackers defend
+    /// and ignores pins, so it is not the same as the legal destinations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color, SquareCoords};
+    ///
+    /// let board = Board::new();
+    /// let threats = board.threat_map(Color::Black);
+    ///
+    /// assert_eq!(threats.len(), 22);
+    /// assert!(threats.contains(&SquareCoords::from_san_str("f6").unwrap()));
+    /// ```
+    pub fn threat_map(&self, by: Color) -> Vec<SquareCoords> {
+        let attacked = self.attacked_squares(by);
+
+        (0..64)
+            .filter_map(SquareCoords::from_index)
+            .filter(|square| attacked[square.0][square.1])
+            .collect()
+    }
+
+    /// Returns the squares the piece located at the given square could move to
+    /// according to its movement rules alone, ignoring whose turn it is and
+    /// whether the move would leave its king in check. This includes pawn
+    /// pushes and captures, but not castles. Returns an empty vector if the
+    /// square is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let board = Board::new();
+    /// let destinations = board.all_piece_moves(SquareCoords::from_san_str("e7").unwrap());
+    ///
+    /// assert_eq!(destinations.len(), 2);
+    /// ```
+    pub fn all_piece_moves(&self, square: SquareCoords) -> Vec<SquareCoords> {
+        movegen::piece_destinations(self, square)
+    }
+
+    /// Returns all the legal moves in the current position in UCI notation,
+    /// in the same format as [Move::to_uci_str()](crate::Move::to_uci_str()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert!(board.legal_moves_uci().contains(&"e2e4".to_string()));
+    /// ```
+    pub fn legal_moves_uci(&self) -> Vec<String> {
+        self.legal_moves().iter().map(Move::to_uci_str).collect()
+    }
+
+    /// Returns all the legal moves in the current position in standard
+    /// algebraic notation, as given by [san_of()](crate::Board::san_of()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert!(board.legal_moves_san().contains(&"Nf3".to_string()));
+    /// ```
+    pub fn legal_moves_san(&self) -> Vec<String> {
+        self.legal_moves_san_pairs()
+            .into_iter()
+            .map(|(_, san)| san)
+            .collect()
+    }
+
+    /// Returns every legal move in the current position together with its
+    /// SAN, as given by [san_of()](crate::Board::san_of()). The legal moves
+    /// are generated once and shared by the disambiguation of all the moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// let pairs = board.legal_moves_san_pairs();
+    ///
+    /// assert_eq!(pairs.len(), 20);
+    /// assert!(pairs.iter().any(|(m, san)| m.to_uci_str() == "g1f3" && san == "Nf3"));
+    /// ```
+    pub fn legal_moves_san_pairs(&self) -> Vec<(Move, String)> {
+        let legal_moves = self.legal_moves();
+
+        legal_moves
+            .iter()
+            .map(|r#move| (*r#move, self.san_among(r#move, &legal_moves)))
+            .collect()
+    }
+
+    /// Returns the legal moves in the current position keyed by their SAN,
+    /// as given by [san_of()](crate::Board::san_of()), so move input can be
+    /// resolved with a lookup. Every legal move has its own key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// let moves = board.san_move_map();
+    ///
+    /// assert_eq!(moves.len(), 20);
+    /// assert_eq!(moves["Nf3"].to_uci_str(), "g1f3");
+    /// ```
+    pub fn san_move_map(&self) -> HashMap<String, Move> {
+        self.legal_moves_san_pairs()
+            .into_iter()
+            .map(|(r#move, san)| (san, r#move))
+            .collect()
+    }
+
+    /// Returns true if the given move puts the opponent's king in check. The
+    /// resulting position is probed, so discovered checks are detected as
+    /// well. The move passed to this method is assumed to be legal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/4B3/8/8/K3R3 w - - 0 1").unwrap();
+    /// let r#move = Move::from_san("Bd5", &board).unwrap();
+    /// assert_eq!(board.gives_check(&r#move), true);
+    /// ```
+    pub fn gives_check(&self, r#move: &Move) -> bool {
+        let mut cloned_board = self.for_search();
+        cloned_board.apply_move(r#move);
+        cloned_board.check()
+    }
+
+    /// Returns the [standard algebraic notation](https://www.chess.com/terms/chess-notation)
+    /// of a move in the current position, including disambiguation and check
+    /// or checkmate markers. The move passed to this method is assumed to be
+    /// legal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/4B3/1B6/8/K3R3 w - - 0 1").unwrap();
+    /// let r#move = Move::from_uci("e4d5", &board).unwrap();
+    /// assert_eq!(board.san_of(&r#move), "Bed5+");
+    /// ```
+    pub fn san_of(&self, r#move: &Move) -> String {
+        self.san_among(r#move, &self.legal_moves())
+    }
+
+    /// Returns the SAN of a move, like [san_of()](crate::Board::san_of()),
+    /// disambiguating it among the given legal moves of the position.
+    fn san_among(&self, r#move: &Move, legal_moves: &[Move]) -> String {
+        let mut san = match r#move.castle {
+            Some(castle) => castle.to_san_str(),
+            None => self.san_body(r#move, legal_moves),
+        };
+
+        let mut cloned_board = self.for_search();
+        cloned_board.apply_move(r#move);
+
+        if cloned_board.checkmate() {
+            san.push('#');
+        } else if cloned_board.check() {
+            san.push('+');
+        }
+
+        san
+    }
+
+    /// Returns a copy of the board without the history of the positions
+    /// played before the current one, which makes it much cheaper to clone
+    /// and to make moves on during a search. Everything else, including
+    /// the legal moves, is the same; only repetitions of positions played
+    /// before the copy was made are no longer detected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let mut board = Board::new();
+    /// board.make_move("e4");
+    /// let search_board = board.for_search();
+    ///
+    /// assert_eq!(search_board.position_history.len(), 1);
+    /// assert_eq!(search_board.legal_moves(), board.legal_moves());
+    /// ```
+    pub fn for_search(&self) -> Board {
+        Board {
+            squares: self.squares,
+            active_color: self.active_color,
+            castle_rights: self.castle_rights.clone(),
+            en_passant_target: self.en_passant_target,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            position_history: self.position_history.last().cloned().into_iter().collect(),
+            white_king_moved: self.white_king_moved,
+            black_king_moved: self.black_king_moved,
+            legal_moves_cache: LegalMovesCache::default(),
+            undo_stack: None,
+        }
+    }
+
+    /// Returns a copy of the board with the given color to move. If the color
+    /// is not the current active color, the en passant target is cleared since
+    /// it only applies to the player who moves next. Useful for asking what
+    /// the other player could do if it were their turn. The moves made on the
+    /// board can't be taken back on the copy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color};
+    ///
+    /// let board = Board::new();
+    /// let board = board.with_active_color(Color::Black);
+    /// assert_eq!(board.legal_moves().len(), 20);
+    /// ```
+    pub fn with_active_color(&self, color: Color) -> Board {
+        let mut board = self.clone();
+
+        if board.active_color != color {
+            board.active_color = color;
+            board.en_passant_target = None;
+        }
+
+        // the moves made so far were played with the other turn order
+        board.clear_undo_stack();
+
+        board
+    }
+
+    /// Returns true if the piece located at the given square is defended by
+    /// another piece of its own color. Returns false if the square is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.is_defended(SquareCoords::from_san_str("e2").unwrap()), true);
+    /// assert_eq!(board.is_defended(SquareCoords::from_san_str("a1").unwrap()), false);
+    /// ```
+    pub fn is_defended(&self, square: SquareCoords) -> bool {
+        match self.get_piece(square) {
+            Some(piece) => !self.square_attackers_by(square, *piece.color()).is_empty(),
+            None => false,
+        }
+    }
+
+    /// Returns the pieces attacking and the pieces defending the piece located
+    /// at the given square, together with their square coordinates. Both
+    /// vectors are empty if the square is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, SquareCoords};
+    ///
+    /// let board = Board::from_fen("4k3/8/3p4/4N3/8/8/8/4K3 w - - 0 1").unwrap();
+    /// let (attackers, defenders) = board.exchange_info(SquareCoords::from_san_str("e5").unwrap());
+    ///
+    /// assert_eq!(attackers.len(), 1);
+    /// assert_eq!(defenders.len(), 0);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn exchange_info(
+        &self,
+        square: SquareCoords,
+    ) -> (Vec<(Piece, SquareCoords)>, Vec<(Piece, SquareCoords)>) {
+        match self.get_piece(square) {
+            Some(piece) => (
+                self.square_attackers_by(square, piece.color().invert()),
+                self.square_attackers_by(square, *piece.color()),
+            ),
+            None => (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// Returns the static exchange evaluation of the given move in pawn units:
+    /// the material won or lost on the destination square assuming both sides
+    /// keep recapturing there with their least valuable piece for as long as
+    /// it is worth it. Pins are not taken into account. Returns 0 for castles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board = Board::from_fen("4k3/8/2p5/3p4/8/8/3R4/4K3 w - - 0 1").unwrap();
+    /// let r#move = Move::from_san("Rxd5", &board).unwrap();
+    ///
+    /// assert_eq!(board.see(&r#move), -4);
+    /// ```
+    pub fn see(&self, r#move: &Move) -> i32 {
+        let (piece, src_square, dst_square) =
+            match (r#move.piece, r#move.src_square, r#move.dst_square) {
+                (Some(piece), Some(src_square), Some(dst_square)) => {
+                    (piece, src_square, dst_square)
+                }
+                _ => return 0,
+            };
+
+        let mut board = self.for_search();
+        let mut gains = vec![0];
+
+        if let Some(capture_square) = self.capture_square(r#move) {
+            gains[0] = self.get_piece(capture_square).map_or(0, |p| p.value());
+            board.set_piece(capture_square, None);
+        }
+
+        let mut piece_on_square = r#move.promotion.unwrap_or(piece);
+        gains[0] += piece_on_square.value() - piece.value();
+        board.set_piece(src_square, None);
+        board.set_piece(dst_square, Some(piece_on_square));
+
+        let mut color = self.active_color.invert();
+
+        loop {
+            // the king is always the last piece to recapture
+            let attacker = board
+                .square_attackers_by(dst_square, color)
+                .into_iter()
+                .min_by_key(|(p, _)| (matches!(p, Piece::King(_)), p.value()));
+
+            let (attacker, attacker_square) = match attacker {
+                Some(attacker) => attacker,
+                None => break,
+            };
+
+            board.set_piece(attacker_square, None);
+
+            // the king can't recapture on a defended square
+            if let Piece::King(_) = attacker {
+                if !board
+                    .square_attackers_by(dst_square, color.invert())
+                    .is_empty()
+                {
+                    break;
+                }
+            }
+
+            gains.push(piece_on_square.value() - gains[gains.len() - 1]);
+            board.set_piece(dst_square, Some(attacker));
+            piece_on_square = attacker;
+            color = color.invert();
+        }
+
+        // going backwards, each side can choose to stop recapturing if that
+        // is better for them
+        while gains.len() > 1 {
+            let gain = gains.pop().unwrap_or(0);
+            let last = gains.len() - 1;
+            gains[last] = -(-gains[last]).max(gain);
+        }
+
+        gains[0]
+    }
+
+    /// Returns the SAN line of the given sequence of UCI moves played from
+    /// the current position, with move numbers (e.g. "12. Nf3 Nc6 13. Bb5").
+    /// A line starting with a black move is numbered with "..." (e.g.
+    /// "12... Nc6 13. Bb5"). Returns `None` if any of the moves is invalid or
+    /// illegal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(
+    ///     board.san_line(&["e2e4", "e7e5", "g1f3"]),
+    ///     Some("1. e4 e5 2. Nf3".to_string())
+    /// );
+    /// ```
+    pub fn san_line(&self, ucis: &[&str]) -> Option<String> {
+        let mut board = self.clone();
+        let mut line = String::new();
+
+        for (i, uci) in ucis.iter().enumerate() {
+            let r#move = Move::from_uci(uci, &board)?;
+            if !board.legal_moves().contains(&r#move) {
+                return None;
+            }
+
+            match board.active_color {
+                Color::White => line.push_str(&format!("{}. ", board.fullmove_number)),
+                Color::Black if i == 0 => line.push_str(&format!("{}... ", board.fullmove_number)),
+                Color::Black => (),
+            }
+
+            line.push_str(&board.san_of(&r#move));
+            line.push(' ');
+            board.apply_move(&r#move);
+        }
+
+        line.pop(); // remove the last space
+
+        Some(line)
+    }
+
+    /// Reads the principal variation reported by a UCI engine (the moves
+    /// following `pv` in an `info` line, e.g. "e2e4 e7e5 g1f3") as moves
+    /// played one after the other from the current position. Reading stops
+    /// at the first move that is invalid or illegal, returning the moves
+    /// before it, or `None` if not even the first move is legal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// let pv = board.parse_uci_pv("e2e4 e7e5 g1f3 e2e4").unwrap();
+    ///
+    /// assert_eq!(pv.len(), 3);
+    /// assert_eq!(pv[2].to_uci_str(), "g1f3");
+    /// ```
+    pub fn parse_uci_pv(&self, pv: &str) -> Option<Vec<Move>> {
+        let mut board = self.for_search();
+        let mut moves = Vec::new();
+
+        for uci in pv.split_whitespace() {
+            match Move::from_uci(uci, &board) {
+                Some(r#move) if board.push(&r#move) => moves.push(r#move),
+                _ => break,
+            }
+        }
+
+        if moves.is_empty() {
+            return None;
+        }
+
+        Some(moves)
+    }
+
+    /// Returns a map of every occupied square to the piece standing on it.
+    /// Empty squares are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color, Piece, SquareCoords};
+    ///
+    /// let board = Board::new();
+    /// let piece_map = board.piece_map();
+    ///
+    /// assert_eq!(piece_map.len(), 32);
+    /// assert_eq!(
+    ///     piece_map.get(&SquareCoords::from_san_str("e1").unwrap()),
+    ///     Some(&Piece::King(Color::White))
+    /// );
+    /// ```
+    pub fn piece_map(&self) -> HashMap<SquareCoords, Piece> {
+        self.pieces().collect()
+    }
+
+    /// Returns an iterator over all the pieces on the board together with the
+    /// square they are located at, going from the 8th rank to the 1st and from
+    /// the a-file to the h-file.
+    pub(crate) fn pieces(&self) -> impl Iterator<Item = (SquareCoords, Piece)> + '_ {
+        self.squares.iter().enumerate().flat_map(|(row, pieces)| {
+            pieces
+                .iter()
+                .enumerate()
+                .filter_map(move |(col, &piece)| piece.map(|p| (SquareCoords(row, col), p)))
+        })
+    }
+
+    /// Returns the set of occupied squares as a [BitBoard].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.occupied().iter_squares().count(), 32);
+    /// ```
+    pub fn occupied(&self) -> BitBoard {
+        self.pieces().map(|(square, _)| square).collect()
+    }
+
+    /// Returns the squares occupied by the given piece, in the same order as
+    /// [pieces_of()](crate::Board::pieces_of()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color, Piece, SquareCoords};
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert_eq!(
+    ///     board.find(Piece::King(Color::Black)),
+    ///     vec![SquareCoords::from_san_str("e8").unwrap()]
+    /// );
+    /// ```
+    pub fn find(&self, piece: Piece) -> Vec<SquareCoords> {
+        self.pieces()
+            .filter(|(_, p)| p == &piece)
+            .map(|(square, _)| square)
+            .collect()
+    }
+
+    /// Returns an iterator over the pieces of the given color together with
+    /// the square they are located at, in the same order as the board is laid
+    /// out, from the 8th rank to the 1st and from the a-file to the h-file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Color};
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert_eq!(board.pieces_of(Color::White).count(), 16);
+    /// ```
+    pub fn pieces_of(&self, color: Color) -> impl Iterator<Item = (SquareCoords, Piece)> + '_ {
+        self.pieces()
+            .filter(move |(_, piece)| piece.color() == &color)
+    }
+
+    /// Returns the material on the board in the format used to name endgame
+    /// tablebases, e.g. "KQvKR": the white pieces followed by the black ones,
+    /// each side ordered from the king down to the pawns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.material_signature(), "KRvK");
+    /// ```
+    pub fn material_signature(&self) -> String {
+        let side_signature = |color: Color| {
+            let mut pieces = self
+                .pieces_of(color)
+                .map(|(_, piece)| piece.to_san_char())
+                .collect::<Vec<_>>();
+
+            pieces.sort_by_key(|&c| "KQRBNP".find(c));
+            pieces.into_iter().collect::<String>()
+        };
+
+        format!(
+            "{}v{}",
+            side_signature(Color::White),
+            side_signature(Color::Black)
+        )
+    }
+
+    /// Returns the phase of the game from the non-pawn material left on the
+    /// board, for tapered evaluations: knights and bishops count 1, rooks 2
+    /// and queens 4, and the sum is clamped to 24. The starting position has
+    /// a phase of 24 and positions with only kings and pawns a phase of 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/4P3/3QK3 w - - 0 1").unwrap();
+    /// assert_eq!(board.game_phase(), 4);
+    /// ```
+    pub fn game_phase(&self) -> u8 {
+        let phase: u32 = self
+            .pieces()
+            .map(|(_, piece)| match piece {
+                Piece::Knight(_) | Piece::Bishop(_) => 1,
+                Piece::Rook(_) => 2,
+                Piece::Queen(_) => 4,
+                Piece::Pawn(_) | Piece::King(_) => 0,
+            })
+            .sum();
+
+        phase.min(24) as u8
+    }
+
+    /// Checks that the number of pieces of each side can be reached in a game:
+    /// no more than 16 pieces, king included, and no more than 8 pawns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, BoardError, Color};
+    ///
+    /// let mut board = Board::from_fen("4k3/8/8/8/8/8/PPPPPPPP/4K3 w - - 0 1").unwrap();
+    /// assert!(board.validate_material().is_ok());
+    ///
+    /// board.squares[5][0] = board.squares[6][0];
+    /// assert_eq!(
+    ///     board.validate_material(),
+    ///     Err(BoardError::TooManyPawns(Color::White))
+    /// );
+    /// ```
+    pub fn validate_material(&self) -> Result<(), BoardError> {
+        for color in [Color::White, Color::Black] {
+            let pieces = self.pieces_of(color).count();
+            let pawns = self
+                .pieces_of(color)
+                .filter(|(_, piece)| piece == &Piece::Pawn(color))
+                .count();
+
+            if pieces > 16 {
+                return Err(BoardError::TooManyPieces(color));
+            }
+
+            if pawns > 8 {
+                return Err(BoardError::TooManyPawns(color));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the material on the board packed into an integer, so positions
+    /// can be grouped by material cheaply. The white pieces take the 16
+    /// lowest bits and the black pieces the 16 highest; within each half the
+    /// number of pawns takes bits 0-3, then 3 bits each for the knights,
+    /// bishops, rooks and queens. Kings are not counted. A count of more than
+    /// 7 pieces of the same kind, only possible after several promotions, is
+    /// stored as 7.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.material_hash(), 1 << 10);
+    /// ```
+    pub fn material_hash(&self) -> u32 {
+        let mut counts = [[0u32; 5]; 2];
+
+        for (_, piece) in self.pieces() {
+            let kind = match piece {
+                Piece::Pawn(_) => 0,
+                Piece::Knight(_) => 1,
+                Piece::Bishop(_) => 2,
+                Piece::Rook(_) => 3,
+                Piece::Queen(_) => 4,
+                Piece::King(_) => continue,
+            };
+
+            counts[piece.color().index()][kind] += 1;
+        }
+
+        let side_hash = |counts: &[u32; 5]| {
+            counts[1..]
+                .iter()
+                .enumerate()
+                .fold(counts[0].min(15), |hash, (i, &count)| {
+                    hash | count.min(7) << (4 + 3 * i)
+                })
+        };
+
+        side_hash(&counts[Color::White.index()]) | side_hash(&counts[Color::Black.index()]) << 16
+    }
+
+    /// Returns a drawing of the board in the given [RenderStyle]. The
+    /// [Unicode](RenderStyle::Unicode) style is the same drawing printed by
+    /// the `Display` implementation, the other styles are meant for terminals
+    /// and fonts without box-drawing characters or chess pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, RenderStyle};
+    ///
+    /// let board = Board::new();
+    /// let drawing = board.render(RenderStyle::AsciiLetters);
+    ///
+    /// assert_eq!(drawing.lines().nth(1), Some("| r | n | b | q | k | b | n | r | 8"));
+    /// assert_eq!(board.render(RenderStyle::Unicode), board.to_string());
+    /// ```
+    pub fn render(&self, style: RenderStyle) -> String {
+        let mut drawing = String::new();
+        // writing into a String never fails
+        let _ = self.write_drawing(&mut drawing, style, false);
+        drawing
+    }
+
+    /// Returns the same drawing of the board as its `Display` implementation,
+    /// but with the rank numbers on both the left and right sides and the
+    /// file letters both above and below the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::new();
+    /// let drawing = board.to_string_coordinates_all();
+    ///
+    /// assert_eq!(drawing.lines().next(), Some("    a   b   c   d   e   f   g   h"));
+    /// ```
+    pub fn to_string_coordinates_all(&self) -> String {
+        let mut drawing = String::new();
+        // writing into a String never fails
+        let _ = self.write_drawing(&mut drawing, RenderStyle::Unicode, true);
+        drawing
+    }
+
+    /// Returns a new board with the ranks reversed, so that the 1st rank
+    /// becomes the 8th and vice versa. Piece colors and the active color are
+    /// left untouched, which means the resulting position may not be a legal
+    /// chess position. Castle rights are dropped since kings and rooks are no
+    /// longer on their starting squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+    /// assert_eq!(board.flip_vertical().fen(), "4K3/8/8/4P3/8/8/8/4k3 b - e6 0 1");
+    /// ```
+    pub fn flip_vertical(&self) -> Board {
+        self.flipped(|s| SquareCoords(7 - s.0, s.1))
+    }
+
+    /// Returns a new board with the files reversed, so that the a-file
+    /// becomes the h-file and vice versa. Piece colors and the active color
+    /// are left untouched, which means the resulting position may not be a
+    /// legal chess position. Castle rights are dropped since kings and rooks
+    /// are no longer on their starting squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+    /// assert_eq!(board.flip_horizontal().fen(), "3k4/8/8/8/3P4/8/8/3K4 b - d3 0 1");
+    /// ```
+    pub fn flip_horizontal(&self) -> Board {
+        self.flipped(|s| SquareCoords(s.0, 7 - s.1))
+    }
+
+    /// Returns the piece located at the given square, if any. If the square
+    /// provided is out of bounds, the method will panic.
+    pub(crate) fn get_piece(&self, square_coords: SquareCoords) -> Option<Piece> {
+        self.squares[square_coords.0][square_coords.1]
+    }
+
+    /// Sets the piece at the given square. To remove a piece from a square,
+    /// pass `None` as the piece. If the square provided is out of bounds, the
+    /// method will panic.
+    pub(crate) fn set_piece(&mut self, square_coords: SquareCoords, piece: Option<Piece>) {
+        self.legal_moves_cache.clear();
+        self.squares[square_coords.0][square_coords.1] = piece;
+    }
+
+    /// Applies a move on the board, updating the board state.
+    /// This method assumes that the move is legal and valid, otherwise
+    /// undefined behavior may occur.
+    pub(crate) fn apply_move(&mut self, r#move: &Move) {
+        self.legal_moves_cache.clear();
+
+        // only record the move if it can be taken back
+        let undo = self.undo_stack.is_some().then(|| self.undo_record(r#move));
+
+        // handle castling, which is neither a pawn move nor a capture
+        if let Some(ref castle) = r#move.castle {
+            match castle {
+                CastleKind::Kingside => self.castle_kingside(),
+                CastleKind::Queenside => self.castle_queenside(),
+            }
+
+            self.halfmove_clock += 1;
+        }
+
+        // handle normal move and en passant
+        if let (Some(src_square), Some(dst_square)) = (r#move.src_square, r#move.dst_square) {
+            // handle en pasant capture
+            if let Some(capture_square) = self.capture_square(r#move) {
+                if capture_square != dst_square {
+                    self.set_piece(capture_square, None);
+                }
+            }
+
+            // reset halfmove clock if a pawn is moved or a piece is captured
+            if r#move.piece == Some(Piece::Pawn(self.active_color)) || r#move.capture {
+                self.halfmove_clock = 0;
+            } else {
+                self.halfmove_clock += 1;
+            }
+
+            // handle promotion
+            if let Some(promotion_piece) = r#move.promotion {
+                self.set_piece(dst_square, Some(promotion_piece));
+            } else {
+                self.set_piece(dst_square, r#move.piece);
+            }
+
+            self.set_piece(src_square, None);
+        }
+
+        self.update_castle_rights(r#move);
+        self.en_passant_target = self.update_en_passant_target_square(r#move);
+
+        if let (Some(undo_stack), Some(undo)) = (&mut self.undo_stack, undo) {
+            undo_stack.push(undo);
+        }
+
+        // the fullmove number goes up after each black move, look at the color
+        // that moved instead of relying on the order of the updates
+        if r#move.color == Color::Black {
+            self.fullmove_number += 1;
+        }
+
+        self.active_color = self.active_color.invert();
+
+        // record the position once the board state is fully updated. The
+        // previous FEN has about the same length, so the string is allocated
+        // once with enough room instead of growing while it is written
+        let capacity = self.position_history.last().map_or(0, String::len) + 8;
+        let mut fen = String::with_capacity(capacity);
+        self.write_fen(&mut fen);
+        self.position_history.push(fen);
+    }
+
+    /// Returns what the given move is about to change on the board, so it can
+    /// be taken back with [undo()](crate::Board::undo()). The castle rights
+    /// are only copied if the move can change them.
+    fn undo_record(&self, r#move: &Move) -> Undo {
+        let corners = [(0, 0), (0, 7), (7, 0), (7, 7)];
+        let changes_castle_rights = !self.castle_rights.is_empty()
+            && (r#move.castle.is_some()
+                || matches!(r#move.piece, Some(Piece::King(_)))
+                || [r#move.src_square, r#move.dst_square]
+                    .iter()
+                    .flatten()
+                    .any(|square| corners.contains(&(square.0, square.1))));
+
+        Undo {
+            r#move: *r#move,
+            captured: self
+                .capture_square(r#move)
+                .and_then(|square| self.get_piece(square).map(|piece| (square, piece))),
+            castle_rights: changes_castle_rights.then(|| self.castle_rights.clone()),
+            en_passant_target: self.en_passant_target,
+            halfmove_clock: self.halfmove_clock,
+            white_king_moved: self.white_king_moved,
+            black_king_moved: self.black_king_moved,
+        }
+    }
+
+    /// Empties the undo stack if recording is enabled, for boards whose
+    /// recorded moves no longer apply.
+    pub(crate) fn clear_undo_stack(&mut self) {
+        if let Some(undo_stack) = &mut self.undo_stack {
+            undo_stack.clear();
+        }
+    }
+
+    /// Returns if a given move will leave the king in check.
+    /// The move passed to this method is assumed to be legal and valid,
+    /// otherwise undefined behavior may occur.
+    pub(crate) fn future_check(&self, r#move: &Move) -> bool {
+        let mut cloned_board = self.for_search();
+        cloned_board.apply_move(r#move);
+        cloned_board.is_king_attacked(self.active_color)
+    }
+
+    /// Returns the pieces an its respectives square coordinates from where a
+    /// given square is being attacked by the opponent of the active color.
+    pub(crate) fn square_attackers(&self, src_square: SquareCoords) -> Vec<(Piece, SquareCoords)> {
+        self.square_attackers_by(src_square, self.active_color.invert())
+    }
+
+    /// Returns the pieces of the given color an its respectives square
+    /// coordinates from where a given square is being attacked. The piece
+    /// standing on the square, if any, is not taken into account, so this can
+    /// be used to find the defenders of a piece as well.
+    pub(crate) fn square_attackers_by(
+        &self,
+        src_square: SquareCoords,
+        color: Color,
+    ) -> Vec<(Piece, SquareCoords)> {
+        let mut attacking_pieces = Vec::new();
+
+        let pieces = [
+            Piece::Pawn(color),
+            Piece::Knight(color),
+            Piece::Bishop(color),
+            Piece::Rook(color),
+            Piece::Queen(color),
+            Piece::King(color),
+        ];
+
+        // starting from the square we are checking, iterate through all the directions
+        // of each piece and check if there are any pieces attacking the square.
+        for piece in &pieces {
+            for direction in &piece.directions() {
+                // pawns can only attack diagonally
+                if piece == &Piece::Pawn(color) && direction.1 == 0 {
+                    continue;
+                }
+
+                let mut src_square = match piece {
+                    // since in this method we are going from the square we are checking to the
+                    // src_square, we need to invert the direction if the piece is a pawn.
+                    Piece::Pawn(_) => SquareCoords(
+                        (src_square.0 as i8 - direction.0) as usize,
+                        (src_square.1 as i8 + direction.1) as usize,
+                    ),
+                    _ => src_square + direction,
+                };
+
+                while src_square.inside_board() {
+                    let src_square_piece = self.get_piece(src_square);
+                    if src_square_piece.is_some_and(|p| &p != piece) {
+                        break;
+                    }
+
+                    if src_square_piece.is_some() {
+                        attacking_pieces.push((*piece, src_square));
+                    }
+
+                    src_square += direction;
+                    match piece {
+                        Piece::Queen(_) => continue,
+                        Piece::Rook(_) => continue,
+                        Piece::Bishop(_) => continue,
+                        Piece::Knight(_) => break,
+                        Piece::King(_) => break,
+                        Piece::Pawn(_) => break,
+                    }
+                }
+            }
+        }
+
+        attacking_pieces
+    }
+
+    /// Returns the SAN of a non-castle move without the check or checkmate
+    /// marker, disambiguating it among the given legal moves.
+    fn san_body(&self, r#move: &Move, legal_moves: &[Move]) -> String {
+        let mut san = String::new();
+
+        // if the move is not a castle, it must have a piece, a source and a
+        // destination square so we can unwrap safely
+        let piece = r#move.piece.unwrap();
+        let src_square = r#move.src_square.unwrap();
+        let dst_square = r#move.dst_square.unwrap();
+        let src_square_str = src_square.to_string();
+
+        if let Piece::Pawn(_) = piece {
+            if r#move.capture {
+                san.push_str(&src_square_str[0..1]);
+            }
+        } else {
+            san.push(piece.to_san_char());
+
+            // other pieces of the same kind that can legally reach the same square
+            let ambiguous_squares: Vec<SquareCoords> = legal_moves
+                .iter()
+                .filter(|m| m.piece == Some(piece) && m.dst_square == Some(dst_square))
+                .filter_map(|m| m.src_square)
+                .filter(|&s| s != src_square)
+                .collect();
+
+            if !ambiguous_squares.is_empty() {
+                if ambiguous_squares.iter().all(|s| s.1 != src_square.1) {
+                    san.push_str(&src_square_str[0..1]);
+                } else if ambiguous_squares.iter().all(|s| s.0 != src_square.0) {
+                    san.push_str(&src_square_str[1..2]);
+                } else {
+                    san.push_str(&src_square_str);
+                }
+            }
+        }
+
+        if r#move.capture {
+            san.push('x');
+        }
+
+        san.push_str(&dst_square.to_string());
+
+        if let Some(promotion) = r#move.promotion {
+            san.push('=');
+            san.push(promotion.to_san_char());
+        }
+
+        san
+    }
+
+    /// Returns a new board with every piece and the en passant target moved to
+    /// the square given by `flip`. Castle rights are dropped and the position
+    /// history starts again from the flipped position, so no move can be
+    /// taken back.
+    fn flipped(&self, flip: impl Fn(SquareCoords) -> SquareCoords) -> Board {
+        let mut board = self.clone();
+        board.squares = [[None; 8]; 8];
+
+        for (square, piece) in self.pieces() {
+            board.set_piece(flip(square), Some(piece));
+        }
+
+        board.en_passant_target = self.en_passant_target.map(flip);
+        board.castle_rights.clear();
+        board.position_history = vec![board.fen()];
+        board.clear_undo_stack();
+
+        board
+    }
+
+    /// Returns the number of times the most repeated position in the position
+    /// history has occurred.
+    fn max_repetitions(&self) -> usize {
+        let mut hash_map = HashMap::new();
+
+        for pos in &self.position_history {
+            *hash_map.entry(position_key(pos)).or_insert(0) += 1;
+        }
+
+        hash_map.into_values().max().unwrap_or(0)
+    }
+
+    /// Returns a canonical view of the position used for equality and hashing.
+    /// Castle rights are turned into a bit mask so their order doesn't matter,
+    /// and the en passant target is only kept if a pawn can capture on it.
+    fn position_identity(&self) -> PositionIdentity {
+        let castle_rights = self
+            .castle_rights
+            .iter()
+            .fold(0, |mask, right| match right {
+                CastleRights::WhiteKingside => mask | 1,
+                CastleRights::WhiteQueenside => mask | 2,
+                CastleRights::BlackKingside => mask | 4,
+                CastleRights::BlackQueenside => mask | 8,
+            });
+
+        (
+            self.squares,
+            self.active_color,
+            castle_rights,
+            self.capturable_en_passant_target(),
+        )
+    }
+
+    /// Returns the en passant target square if a pawn of the active color
+    /// stands next to it and could capture on it, or `None` otherwise.
+    pub(crate) fn capturable_en_passant_target(&self) -> Option<SquareCoords> {
+        self.en_passant_target.filter(|&target| {
+            let row_direction = match self.active_color {
+                Color::White => 1,
+                Color::Black => -1,
+            };
+
+            [(row_direction, 1), (row_direction, -1)]
+                .iter()
+                .any(|direction| {
+                    let src_square = target + direction;
+                    src_square.inside_board()
+                        && self.get_piece(src_square) == Some(Piece::Pawn(self.active_color))
+                })
+        })
+    }
+
+    /// Parses a move in UCI or SAN notation and returns it if it is legal in
+    /// the current position.
+    fn parse_legal_move(&self, move_str: &str) -> Option<Move> {
+        // try to parse the move as UCI.
+        if let Some(r#move) = Move::from_uci(move_str, self) {
+            if self.legal_moves().contains(&r#move) {
+                return Some(r#move);
+            }
+        }
+
+        // try to parse the move as SAN.
+        if let Some(r#move) = Move::from_san(move_str, self) {
+            if self.legal_moves().contains(&r#move) {
+                return Some(r#move);
+            }
+        }
+
+        None
+    }
+
+    /// Returns true if the given square can be the en passant target of the
+    /// current position: the opponent's pawn that just made a double push
+    /// stands right behind it, and both the target and the square the pawn
+    /// came from are empty.
+    fn valid_en_passant_target(&self, target: SquareCoords) -> bool {
+        // rows of the target, of the pushed pawn and of its starting square
+        let (target_row, pawn_row, start_row) = match self.active_color {
+            Color::White => (2, 3, 1),
+            Color::Black => (5, 4, 6),
+        };
+
+        target.0 == target_row
+            && self.get_piece(target).is_none()
+            && self.get_piece(SquareCoords(start_row, target.1)).is_none()
+            && self.get_piece(SquareCoords(pawn_row, target.1))
+                == Some(Piece::Pawn(self.active_color.invert()))
+    }
+
+    /// Writes the drawing of the board in the given style, see
+    /// [render()](crate::Board::render()). With `all_coordinates`, the rank
+    /// numbers are also written on the left and the file letters above, see
+    /// [to_string_coordinates_all()](crate::Board::to_string_coordinates_all()).
+    fn write_drawing(
+        &self,
+        f: &mut impl std::fmt::Write,
+        style: RenderStyle,
+        all_coordinates: bool,
+    ) -> std::fmt::Result {
+        let (first_line, horizontal_line, last_line, vertical) = match style {
+            RenderStyle::Unicode => (
+                "┌───┬───┬───┬───┬───┬───┬───┬───┐",
+                "├───┼───┼───┼───┼───┼───┼───┼───┤",
+                "└───┴───┴───┴───┴───┴───┴───┴───┘",
+                '│',
+            ),
+            RenderStyle::AsciiLetters | RenderStyle::UnicodePiecesAsciiGrid => (
+                "+---+---+---+---+---+---+---+---+",
+                "+---+---+---+---+---+---+---+---+",
+                "+---+---+---+---+---+---+---+---+",
+                '|',
+            ),
+        };
+        let rows = ['8', '7', '6', '5', '4', '3', '2', '1'];
+        let cols = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+
+        // the rank numbers on the left take two characters, so the grid and
+        // the file letters need the same indentation to stay aligned
+        let indent = match all_coordinates {
+            true => "  ",
+            false => "",
+        };
+        let files_line = cols.iter().fold(indent.to_string(), |mut line, col| {
+            line.push_str(&format!("  {} ", col));
+            line
+        });
+
+        if all_coordinates {
+            writeln!(f, "{}", files_line.trim_end())?;
+        }
+
+        writeln!(f, "{}{}", indent, first_line)?;
+
+        for (i, &row) in self.squares.iter().enumerate() {
+            if all_coordinates {
+                write!(f, "{} ", rows[i])?;
+            }
+
+            write!(f, "{}", vertical)?;
+            for &piece in &row {
+                match piece {
+                    Some(piece) if style == RenderStyle::AsciiLetters => {
+                        write!(f, " {} {}", piece.to_fen_char(), vertical)
+                    }
+                    Some(piece) => write!(f, " {} {}", piece, vertical),
+                    None => write!(f, "   {}", vertical),
+                }?;
+            }
+            write!(f, " {}", rows[i])?;
+
+            if i != 7 {
+                writeln!(f, "\n{}{}", indent, horizontal_line)?;
+            } else {
+                writeln!(f, "\n{}{}", indent, last_line)?;
+            }
+        }
+
+        match all_coordinates {
+            true => write!(f, "{}", files_line.trim_end()),
+            false => write!(f, "{}", files_line),
+        }
+    }
+
+    /// Castles kingside for the given active color.
+    /// This method assumes that the castle is legal.
+    fn castle_kingside(&mut self) {
+        let row = match self.active_color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+
+        let king_square = (row, 4).into();
+        let rook_square = (row, 7).into();
+        let new_king_square = (row, 6).into();
+        let new_rook_square = (row, 5).into();
+
+        self.set_piece(king_square, None);
+        self.set_piece(rook_square, None);
+        self.set_piece(new_king_square, Some(Piece::King(self.active_color)));
+        self.set_piece(new_rook_square, Some(Piece::Rook(self.active_color)));
+    }
+
+    /// Castles queenside for the current active color.
+    /// This method assumes that the castle is legal.
+    fn castle_queenside(&mut self) {
+        let row = match self.active_color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+
+        let king_square = (row, 4).into();
+        let rook_square = (row, 0).into();
+        let new_king_square = (row, 2).into();
+        let new_rook_square = (row, 3).into();
+
+        self.set_piece(king_square, None);
+        self.set_piece(rook_square, None);
+        self.set_piece(new_king_square, Some(Piece::King(self.active_color)));
+        self.set_piece(new_rook_square, Some(Piece::Rook(self.active_color)));
+    }
+
+    /// Takes back a castle of the current active color, moving the king and
+    /// the rook back to their starting squares.
+    fn undo_castle(&mut self, castle: CastleKind) {
+        let row = match self.active_color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+
+        let (king_square, rook_square, castled_rook_square) = match castle {
+            CastleKind::Kingside => ((row, 6).into(), (row, 7).into(), (row, 5).into()),
+            CastleKind::Queenside => ((row, 2).into(), (row, 0).into(), (row, 3).into()),
+        };
+
+        self.set_piece(king_square, None);
+        self.set_piece(castled_rook_square, None);
+        self.set_piece((row, 4).into(), Some(Piece::King(self.active_color)));
+        self.set_piece(rook_square, Some(Piece::Rook(self.active_color)));
+    }
+
+    /// Checks if en passant is possible in next turn given a move.
+    fn update_en_passant_target_square(&self, r#move: &Move) -> Option<SquareCoords> {
+        if let (Some(src_square), Some(dst_square)) = (r#move.src_square, r#move.dst_square) {
+            // if the move is not a double pawn move, return false
+            if r#move.piece != Some(Piece::Pawn(self.active_color))
+                || (dst_square.0 as i8 - src_square.0 as i8).abs() != 2
+            {
+                return None;
+            }
+
+            let en_passant_target: SquareCoords = {
+                match self.active_color {
+                    Color::Black => (dst_square.0 - 1, dst_square.1).into(),
+                    Color::White => (dst_square.0 + 1, dst_square.1).into(),
+                }
+            };
+
+            // the pawns that could capture en passant stand next to the
+            // destination square, on the same rank
+            for direction in [(0, 1), (0, -1)] {
+                let src_square = dst_square + direction;
+
+                if !src_square.inside_board() {
+                    continue;
+                }
+
+                if self.get_piece(src_square) == Some(Piece::Pawn(self.active_color.invert())) {
+                    return Some(en_passant_target);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the square of the current active color king.
+    fn king_square(&self) -> SquareCoords {
+        for (row, &col) in self.squares.iter().enumerate() {
+            for (col, &piece) in col.iter().enumerate() {
+                if piece == Some(Piece::King(self.active_color)) {
+                    return SquareCoords(row, col);
+                }
+            }
+        }
+
+        unreachable!("King can't be missing from the battle!")
+    }
+
+    /// Updates the castle rights given a move.
+    fn update_castle_rights(&mut self, r#move: &Move) {
+        // castling move
+        if r#move.castle.is_some() {
+            match self.active_color {
+                Color::White => {
+                    self.white_king_moved = true;
+                    self.castle_rights.retain(|x| {
+                        x != &CastleRights::WhiteKingside && x != &CastleRights::WhiteQueenside
+                    })
+                }
+                Color::Black => {
+                    self.black_king_moved = true;
+                    self.castle_rights.retain(|x| {
+                        x != &CastleRights::BlackKingside && x != &CastleRights::BlackQueenside
+                    })
+                }
+            }
+        }
+
+        // white king moves
+        if r#move.piece.is_some_and(|p| p == Piece::King(Color::White)) {
+            self.white_king_moved = true;
+            self.castle_rights.retain(|x| {
+                x != &CastleRights::WhiteKingside && x != &CastleRights::WhiteQueenside
+            });
+        }
+
+        // black king moves
+        if r#move.piece.is_some_and(|p| p == Piece::King(Color::Black)) {
+            self.black_king_moved = true;
+            self.castle_rights.retain(|x| {
+                x != &CastleRights::BlackKingside && x != &CastleRights::BlackQueenside
+            });
+        }
+
+        // white kingside rook moves or is captured
+        if r#move.src_square.is_some_and(|s| s == (7, 7))
+            || r#move.dst_square.is_some_and(|s| s == (7, 7))
+        {
+            self.castle_rights
+                .retain(|x| x != &CastleRights::WhiteKingside);
+        }
+
+        // white queenside rook moves or is captured
+        if r#move.src_square.is_some_and(|s| s == (7, 0))
+            || r#move.dst_square.is_some_and(|s| s == (7, 0))
+        {
+            self.castle_rights
+                .retain(|x| x != &CastleRights::WhiteQueenside);
+        }
+
+        // black kingside rook moves or is captured
+        if r#move.src_square.is_some_and(|s| s == (0, 7))
+            || r#move.dst_square.is_some_and(|s| s == (0, 7))
+        {
+            self.castle_rights
+                .retain(|x| x != &CastleRights::BlackKingside);
+        }
+
+        // black queenside rook moves or is captured
+        if r#move.src_square.is_some_and(|s| s == (0, 0))
+            || r#move.dst_square.is_some_and(|s| s == (0, 0))
+        {
+            self.castle_rights
+                .retain(|x| x != &CastleRights::BlackQueenside);
+        }
+    }
+}
+
+/// Returns the part of a FEN string that identifies a position for repetition
+/// purposes: piece placement, active color, castle rights and en passant
+/// target. Slicing avoids allocating a new string for each position.
+fn position_key(fen: &str) -> &str {
+    let end = fen.match_indices(' ').nth(3).map_or(fen.len(), |(i, _)| i);
+    &fen[..end]
+}
+
+/// Two boards are equal when they represent the same position: the same piece
+/// placement, active color, castle rights in any order and en passant target,
+/// where a target that no pawn can capture on is ignored. Move counters and
+/// history are not compared.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.positions_equal_ignoring_counters(other)
+    }
+}
+
+impl Eq for Board {}
+
+/// Hashes the same canonical view of the position used by [PartialEq], so
+/// transposed positions land in the same bucket of a `HashSet` or `HashMap`.
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.position_identity().hash(state);
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_drawing(f, RenderStyle::Unicode, false)
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_piece_map() {
+        let board = Board::new();
+        let piece_map = board.piece_map();
+        assert_eq!(piece_map.len(), 32);
+
+        for (square, piece) in [
+            ("a1", Piece::Rook(Color::White)),
+            ("d1", Piece::Queen(Color::White)),
+            ("g1", Piece::Knight(Color::White)),
+            ("e2", Piece::Pawn(Color::White)),
+            ("c8", Piece::Bishop(Color::Black)),
+            ("e8", Piece::King(Color::Black)),
+            ("h7", Piece::Pawn(Color::Black)),
+        ] {
+            let square = SquareCoords::from_san_str(square).unwrap();
+            assert_eq!(piece_map.get(&square), Some(&piece));
+        }
+
+        assert_eq!(
+            piece_map.get(&SquareCoords::from_san_str("e4").unwrap()),
+            None
+        );
+
+        // only occupied squares are present
+        let board = Board::from_fen("8/8/1k6/5K2/8/8/4N3/8 b - - 0 2").unwrap();
+        assert_eq!(board.piece_map().len(), 3);
+    }
+
+    #[test]
+    fn test_legal_moves_sorted() {
+        let board = Board::new();
+        let legal_moves = board.legal_moves_sorted();
+        assert_eq!(legal_moves.len(), 20);
+        assert_eq!(legal_moves[0].to_uci_str(), "b1a3");
+        assert_eq!(legal_moves[19].to_uci_str(), "h2h4");
+        assert_eq!(legal_moves, board.legal_moves_sorted());
+
+        // promotions are ordered queen, rook, bishop, knight and castles are
+        // ordered by the king squares
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let legal_moves = board.legal_moves_sorted();
+        let uci_moves: Vec<String> = legal_moves.iter().map(|m| m.to_uci_str()).collect();
+        let castles: Vec<String> = legal_moves
+            .iter()
+            .filter(|m| m.castle.is_some())
+            .map(|m| m.to_uci_str())
+            .collect();
+        assert_eq!(castles, ["e1c1", "e1g1"]);
+        assert_eq!(
+            uci_moves[uci_moves.len() - 4..],
+            ["b7b8q", "b7b8r", "b7b8b", "b7b8n"]
+        );
+    }
+
+    #[test]
+    fn test_capture_square() {
+        // normal capture
+        let board =
+            Board::from_fen("r1bqkbnr/1p1ppppp/p1n5/1Bp5/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 4")
+                .unwrap();
+        let r#move = Move::from_san("Bxc6", &board).unwrap();
+        assert_eq!(
+            board.capture_square(&r#move),
+            SquareCoords::from_san_str("c6")
+        );
+
+        // en passant capture
+        let board =
+            Board::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")
+                .unwrap();
+        let r#move = Move::from_san("exd6", &board).unwrap();
+        assert_eq!(
+            board.capture_square(&r#move),
+            SquareCoords::from_san_str("d5")
+        );
+
+        // black en passant capture
+        let board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let r#move = Move::from_san("dxe3", &board).unwrap();
+        assert_eq!(
+            board.capture_square(&r#move),
+            SquareCoords::from_san_str("e4")
+        );
+
+        // non capture
+        let board = Board::new();
+        let r#move = Move::from_san("e4", &board).unwrap();
+        assert_eq!(board.capture_square(&r#move), None);
+
+        let r#move = Move::from_san("O-O", &board).unwrap();
+        assert_eq!(board.capture_square(&r#move), None);
+    }
+
+    #[test]
+    fn test_no_moves_reason() {
+        // checkmate
+        let board = Board::from_fen("rnb1kbnr/pppp1ppp/4p3/8/5PPq/8/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+            .unwrap();
+        assert_eq!(board.no_moves_reason(), Some(NoMovesReason::Checkmate));
+
+        // stalemate
+        let board = Board::from_fen("8/7p/8/8/1p6/5k2/5p2/5K2 w - - 4 56").unwrap();
+        assert_eq!(board.no_moves_reason(), Some(NoMovesReason::Stalemate));
+
+        // check with legal moves
+        let board =
+            Board::from_fen("rnbqk1nr/ppp2ppp/4p3/3p4/1bPP4/5N2/PP2PPPP/RNBQKB1R w KQkq - 2 4")
+                .unwrap();
+        assert_eq!(board.no_moves_reason(), None);
+
+        assert_eq!(Board::new().no_moves_reason(), None);
+    }
+
+    #[test]
+    fn test_flip() {
+        let board = Board::new();
+
+        let flipped = board.flip_vertical();
+        assert_eq!(
+            flipped.fen(),
+            "RNBQKBNR/PPPPPPPP/8/8/8/8/pppppppp/rnbqkbnr w - - 0 1"
+        );
+        assert_eq!(
+            flipped.get_piece((0, 4).into()),
+            Some(Piece::King(Color::White))
+        );
+        assert_eq!(
+            flipped.get_piece((7, 3).into()),
+            Some(Piece::Queen(Color::Black))
+        );
+        assert_eq!(flipped.active_color, Color::White);
+
+        let flipped = board.flip_horizontal();
+        assert_eq!(
+            flipped.fen(),
+            "rnbkqbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKQBNR w - - 0 1"
+        );
+        assert_eq!(
+            flipped.get_piece((7, 3).into()),
+            Some(Piece::King(Color::White))
+        );
+        assert_eq!(
+            flipped.get_piece((7, 4).into()),
+            Some(Piece::Queen(Color::White))
+        );
+
+        // flipping twice gives back the same piece placement
+        let board =
+            Board::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")
+                .unwrap();
+        assert_eq!(board.flip_vertical().flip_vertical().squares, board.squares);
+        assert_eq!(
+            board.flip_horizontal().flip_horizontal().squares,
+            board.squares
+        );
+
+        // en passant target
+        assert_eq!(
+            board.flip_vertical().en_passant_target,
+            SquareCoords::from_san_str("d3")
+        );
+        assert_eq!(
+            board.flip_horizontal().en_passant_target,
+            SquareCoords::from_san_str("e6")
+        );
+    }
+
+    #[test]
+    fn test_with_active_color() {
+        // black to move, but we ask for white's moves
+        let board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        let white_board = board.with_active_color(Color::White);
+        assert_eq!(white_board.active_color, Color::White);
+        assert_eq!(white_board.en_passant_target, None);
+        assert_eq!(white_board.legal_moves().len(), 30);
+
+        // the original board is untouched
+        assert_eq!(board.active_color, Color::Black);
+        assert_eq!(board.en_passant_target, SquareCoords::from_san_str("e3"));
+        assert_eq!(board.legal_moves().len(), 20);
+
+        // same color keeps the en passant target
+        let same_board = board.with_active_color(Color::Black);
+        assert_eq!(same_board.en_passant_target, board.en_passant_target);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut board = Board::new();
+        let r#move = *board
+            .legal_moves()
+            .iter()
+            .find(|m| m.to_uci_str() == "e2e4")
+            .unwrap();
+
+        assert!(board.push(&r#move));
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        // the same move is no longer legal
+        assert!(!board.push(&r#move));
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_from_placement() {
+        let board = Board::from_placement("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").unwrap();
+        assert_eq!(board.fen(), Board::new().fen());
+
+        // only kings and rooks on their starting squares keep castle rights
+        let board = Board::from_placement("r3k3/8/8/8/8/8/8/4K2R").unwrap();
+        assert_eq!(board.fen(), "r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1");
+
+        let board = Board::from_placement("8/8/1k6/5K2/8/8/4N3/8").unwrap();
+        assert_eq!(board.fen(), "8/8/1k6/5K2/8/8/4N3/8 w - - 0 1");
+
+        // a full FEN is not a piece placement
+        assert!(Board::from_placement(FEN_STARTING_POSITION).is_err());
+        assert!(Board::from_placement("rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR").is_err());
+    }
+
+    #[test]
+    fn test_legal_moves_notation() {
+        let board = Board::new();
+
+        let uci_moves = board.legal_moves_uci();
+        assert_eq!(uci_moves.len(), 20);
+        assert!(uci_moves.contains(&"e2e4".to_string()));
+        assert!(uci_moves.contains(&"g1f3".to_string()));
+
+        let san_moves = board.legal_moves_san();
+        assert_eq!(san_moves.len(), 20);
+        assert!(san_moves.contains(&"e4".to_string()));
+        assert!(san_moves.contains(&"Nf3".to_string()));
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let san_moves = board.legal_moves_san();
+        assert!(san_moves.contains(&"O-O".to_string()));
+        assert!(san_moves.contains(&"O-O-O".to_string()));
+        assert!(san_moves.contains(&"Rxa8+".to_string()));
+    }
+
+    #[test]
+    fn test_make_move_default_promotion() {
+        let fen = "r1bq2nr/1pp1Pppp/p1np2k1/2b5/2B5/3N4/PPPP1PPP/RNBQK2R w KQ - 0 9";
+
+        // strict UCI doesn't match any legal move without the promotion piece
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.make_move("e7e8"), None);
+        assert_eq!(board.fen(), fen);
+
+        let mut board = Board::from_fen(fen).unwrap();
+        let r#move = board.make_move_default_promotion("e7e8").unwrap();
+        assert_eq!(r#move.promotion, Some(Piece::Queen(Color::White)));
+        assert_eq!(
+            board.get_piece((0, 4).into()),
+            Some(Piece::Queen(Color::White))
+        );
+
+        // explicit promotion pieces are kept
+        let mut board = Board::from_fen(fen).unwrap();
+        let r#move = board.make_move_default_promotion("e7e8n").unwrap();
+        assert_eq!(r#move.promotion, Some(Piece::Knight(Color::White)));
+
+        // other moves behave like make_move
+        let mut board = Board::from_fen(fen).unwrap();
+        assert!(board.make_move_default_promotion("Nf4").is_some());
+    }
+
+    #[test]
+    fn test_legal_moves_grouped() {
+        let board = Board::new();
+        let grouped_moves = board.legal_moves_grouped();
+        assert_eq!(grouped_moves.len(), 10);
+
+        for square in ["a2", "b2", "c2", "d2", "e2", "f2", "g2", "h2", "b1", "g1"] {
+            let square = SquareCoords::from_san_str(square).unwrap();
+            assert_eq!(grouped_moves[&square].len(), 2);
+            assert!(grouped_moves[&square]
+                .iter()
+                .all(|m| m.src_square == Some(square)));
+        }
+
+        // castles are grouped with the king moves
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let grouped_moves = board.legal_moves_grouped();
+        let king_moves = &grouped_moves[&SquareCoords::from_san_str("e1").unwrap()];
+        assert_eq!(king_moves.len(), 7);
+        assert_eq!(king_moves.iter().filter(|m| m.castle.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn test_automatic_draw() {
+        // seventy-five-move rule
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 120").unwrap();
+        assert!(board.seventyfive_move_rule());
+        assert!(board.is_automatic_draw());
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 120").unwrap();
+        assert!(!board.seventyfive_move_rule());
+        assert!(!board.is_automatic_draw());
+
+        // fivefold repetition
+        let mut board = Board::new();
+        board.make_move("e4");
+        board.make_move("e5");
+
+        for i in 1..=4 {
+            for r#move in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                board.make_move(r#move);
+            }
+
+            assert_eq!(board.threefold_repetition(), i >= 2);
+            assert_eq!(board.fivefold_repetition(), i >= 4);
+        }
+
+        assert!(board.is_automatic_draw());
+    }
+
+    #[test]
+    fn test_is_defended() {
+        let board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+                .unwrap();
+        let square = |s| SquareCoords::from_san_str(s).unwrap();
+
+        // e5 pawn defended by the c6 knight
+        assert!(board.is_defended(square("e5")));
+        // e4 pawn is not defended by any white piece
+        assert!(!board.is_defended(square("e4")));
+        // d2 pawn defended by pieces on the back rank
+        assert!(board.is_defended(square("d2")));
+        // f3 knight defended diagonally by the g2 pawn and by the queen
+        assert!(board.is_defended(square("f3")));
+        // empty square
+        assert!(!board.is_defended(square("d4")));
+
+        // pawns defend diagonally but not forward
+        let board = Board::from_fen("4k3/8/8/8/3P4/3P4/8/4K3 w - - 0 1").unwrap();
+        assert!(!board.is_defended(square("d4")));
+        let board = Board::from_fen("4k3/8/8/8/3P4/2P5/8/4K3 w - - 0 1").unwrap();
+        assert!(board.is_defended(square("d4")));
+    }
+
+    #[test]
+    fn test_exchange_info() {
+        let square = |s| SquareCoords::from_san_str(s).unwrap();
+
+        // hanging knight attacked by a pawn and a queen
+        let board = Board::from_fen("4k3/8/3p1q2/4N3/8/8/8/4K3 w - - 0 1").unwrap();
+        let (attackers, defenders) = board.exchange_info(square("e5"));
+        assert_eq!(
+            attackers,
+            vec![
+                (Piece::Pawn(Color::Black), square("d6")),
+                (Piece::Queen(Color::Black), square("f6")),
+            ]
+        );
+        assert!(defenders.is_empty());
+
+        // the same knight defended by a pawn and a rook
+        let board = Board::from_fen("4k3/8/3p1q2/4N3/3P4/8/8/4RK2 w - - 0 1").unwrap();
+        let (attackers, defenders) = board.exchange_info(square("e5"));
+        assert_eq!(attackers.len(), 2);
+        assert_eq!(
+            defenders,
+            vec![
+                (Piece::Pawn(Color::White), square("d4")),
+                (Piece::Rook(Color::White), square("e1")),
+            ]
+        );
+
+        // empty square
+        assert_eq!(board.exchange_info(square("a4")), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_write_fen() {
+        let mut buf = String::new();
+
+        for fen in [
+            FEN_STARTING_POSITION,
+            "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+            "8/8/1k6/5K2/8/8/4N3/8 b - - 12 52",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+
+            buf.clear();
+            board.write_fen(&mut buf);
+            assert_eq!(buf, board.fen());
+            assert_eq!(buf, fen);
+        }
+
+        // appends to the existing content
+        let mut buf = "fen: ".to_string();
+        Board::new().write_fen(&mut buf);
+        assert_eq!(buf, format!("fen: {}", FEN_STARTING_POSITION));
+    }
+
+    #[test]
+    fn test_king_has_moved() {
+        // the king moves out and back
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        for r#move in ["Kd1", "Kd8", "Ke1", "Ke8"] {
+            board.make_move(r#move);
+        }
+        assert!(board.castle_rights.is_empty());
+        assert!(board.king_has_moved(Color::White));
+        assert!(board.king_has_moved(Color::Black));
+
+        // both rooks move, but the kings stay
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        for r#move in ["Rb1", "Rb8", "Rg1", "Rg8"] {
+            board.make_move(r#move);
+        }
+        assert!(board.castle_rights.is_empty());
+        assert!(!board.king_has_moved(Color::White));
+        assert!(!board.king_has_moved(Color::Black));
+
+        // castling moves the king
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        board.make_move("O-O");
+        assert!(board.king_has_moved(Color::White));
+        assert!(!board.king_has_moved(Color::Black));
+
+        // king away from its starting square in the FEN
+        let board = Board::from_fen("8/8/1k6/5K2/8/8/4N3/8 b - - 0 2").unwrap();
+        assert!(board.king_has_moved(Color::White));
+        assert!(board.king_has_moved(Color::Black));
+    }
+
+    #[test]
+    fn test_san_line() {
+        // line starting with a black move
+        let board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        assert_eq!(
+            board.san_line(&["e7e5", "g1f3", "b8c6", "f1b5"]),
+            Some("1... e5 2. Nf3 Nc6 3. Bb5".to_string())
+        );
+
+        // numbering starts from the fullmove number
+        let board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 12")
+                .unwrap();
+        assert_eq!(
+            board.san_line(&["f1b5", "g8f6", "e1g1"]),
+            Some("12. Bb5 Nf6 13. O-O".to_string())
+        );
+
+        // the board is left untouched
+        assert_eq!(board.fullmove_number, 12);
+
+        // illegal and invalid moves
+        assert_eq!(board.san_line(&["f1b5", "e8e7", "e1e3"]), None);
+        assert_eq!(board.san_line(&["f1b5", "xyz"]), None);
+        assert_eq!(board.san_line(&[]), Some(String::new()));
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        // king vs king
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.insufficient_material());
+
+        // king and knight vs king
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4KN2 w - - 0 1").unwrap();
+        assert!(board.insufficient_material());
+
+        // two opposite colored bishops of the same side vs king
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+        assert!(!board.insufficient_material());
+
+        // one bishop each on squares of the same color
+        let board = Board::from_fen("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+        assert!(board.insufficient_material());
+
+        // one bishop each on squares of different colors
+        let board = Board::from_fen("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+        assert!(!board.insufficient_material());
+
+        // two knights vs king
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1").unwrap();
+        assert!(!board.insufficient_material());
+
+        // knight vs bishop
+        let board = Board::from_fen("4kb2/8/8/8/8/8/8/4K1N1 w - - 0 1").unwrap();
+        assert!(!board.insufficient_material());
+
+        // a single pawn
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert!(!board.insufficient_material());
+    }
+
+    #[test]
+    fn test_legal_destinations() {
+        let square = |s| SquareCoords::from_san_str(s).unwrap();
+
+        // boxed in king
+        let board = Board::new();
+        assert!(board.legal_destinations(square("e1")).is_empty());
+
+        // developed knight
+        let board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/7N/PPPPPPPP/RNBQKB1R b KQkq - 1 1").unwrap();
+        let board = board.with_active_color(Color::White);
+        assert_eq!(
+            board.legal_destinations(square("h3")),
+            vec![square("g1"), square("g5"), square("f4")]
+        );
+
+        // castling squares
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let destinations = board.legal_destinations(square("e1"));
+        assert_eq!(destinations.len(), 7);
+        assert!(destinations.contains(&square("g1")));
+        assert!(destinations.contains(&square("c1")));
+
+        // promotions are not repeated
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.legal_destinations(square("b7")), vec![square("b8")]);
+
+        // empty square or opponent piece
+        assert!(board.legal_destinations(square("a4")).is_empty());
+        assert!(board.legal_destinations(square("e8")).is_empty());
+    }
+
+    #[test]
+    fn test_turn() {
+        let mut board = Board::new();
+        assert_eq!(board.turn(), Color::White);
+        assert!(board.is_whites_turn());
+
+        board.make_move("e4");
+        assert_eq!(board.turn(), Color::Black);
+        assert!(!board.is_whites_turn());
+    }
+
+    #[test]
+    fn test_rank_fen() {
+        let board = Board::new();
+        assert_eq!(board.rank_fen(1), "RNBQKBNR");
+        assert_eq!(board.rank_fen(2), "PPPPPPPP");
+        assert_eq!(board.rank_fen(5), "8");
+        assert_eq!(board.rank_fen(8), "rnbqkbnr");
+
+        let board =
+            Board::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")
+                .unwrap();
+        assert_eq!(board.rank_fen(5), "3pP3");
+        assert_eq!(board.rank_fen(6), "p7");
+        assert_eq!(board.rank_fen(7), "1pp1pppp");
+    }
+
+    #[test]
+    fn test_make_uci_move_en_passant() {
+        // white captures en passant
+        let mut board =
+            Board::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")
+                .unwrap();
+        let r#move = board.make_uci_move("e5d6").unwrap();
+        assert!(r#move.capture);
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+        );
+
+        // black captures en passant
+        let mut board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        assert!(board.make_uci_move("d4e3").is_some());
+        assert_eq!(board.fen(), "4k3/8/8/8/8/4p3/8/4K3 w - - 0 2");
+    }
+
+    #[test]
+    fn test_mate_in() {
+        // mate in 1
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        let r#move = board.mate_in(1).unwrap();
+        assert_eq!(board.san_of(&r#move), "Rh8#");
+        assert_eq!(board.mate_in(0), None);
+
+        // mate in 2: Kc7 forces Ka7 and then Ra1#
+        let board = Board::from_fen("k7/8/2K5/8/8/8/8/1R6 w - - 0 1").unwrap();
+        assert_eq!(board.mate_in(1), None);
+
+        let r#move = board.mate_in(2).unwrap();
+        let mut board = board.clone();
+        board.apply_move(&r#move);
+        for reply in board.legal_moves() {
+            let mut board = board.clone();
+            board.apply_move(&reply);
+            assert!(board.mate_in(1).is_some());
+        }
+
+        // stalemating moves are not mates
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(board.mate_in(2), None);
+    }
+
+    #[test]
+    fn test_repetition_count() {
+        let mut board = Board::new();
+        board.make_move("e4");
+        board.make_move("e5");
+        assert_eq!(board.repetition_count(), 1);
+
+        for r#move in ["Nf3", "Nf6", "Ng1"] {
+            board.make_move(r#move);
+            assert_eq!(board.repetition_count(), 1);
+        }
+
+        board.make_move("Ng8");
+        assert_eq!(board.repetition_count(), 2);
+
+        for r#move in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            board.make_move(r#move);
+        }
+        assert_eq!(board.repetition_count(), 3);
+        assert!(board.threefold_repetition());
+
+        // a new position
+        board.make_move("Nc3");
+        assert_eq!(board.repetition_count(), 1);
+    }
+
+    #[test]
+    fn test_all_piece_moves() {
+        let square = |s: &str| SquareCoords::from_san_str(s).unwrap();
+        let squares = |v: &[&str]| v.iter().map(|s| square(s)).collect::<Vec<_>>();
+
+        // a pinned rook still moves along its lines
+        let board = Board::from_fen("4r1k1/8/8/8/8/8/4R3/4K3 b - - 0 1").unwrap();
+        let mut destinations = board.all_piece_moves(square("e2"));
+        destinations.sort_by_key(|s| (s.0, s.1));
+        assert_eq!(
+            destinations,
+            squares(&[
+                "e8", "e7", "e6", "e5", "e4", "e3", "a2", "b2", "c2", "d2", "f2", "g2", "h2"
+            ])
+        );
+
+        // pawn pushes and captures, not on its turn
+        let board = Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2")
+            .unwrap();
+        let mut destinations = board.all_piece_moves(square("e4"));
+        destinations.sort_by_key(|s| (s.0, s.1));
+        assert_eq!(destinations, squares(&["d5", "e5"]));
+
+        let mut destinations = board.all_piece_moves(square("c2"));
+        destinations.sort_by_key(|s| (s.0, s.1));
+        assert_eq!(destinations, squares(&["c4", "c3"]));
+
+        // blocked pawn
+        let board = Board::from_fen("4k3/8/8/8/8/4n3/4P3/4K3 w - - 0 1").unwrap();
+        assert!(board.all_piece_moves(square("e2")).is_empty());
+
+        // king, moving next to the opponent king is not filtered out
+        let board = Board::from_fen("8/8/8/8/8/4k3/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.all_piece_moves(square("e1")).len(), 5);
+
+        // empty square
+        assert!(board.all_piece_moves(square("a1")).is_empty());
+    }
+
+    #[test]
+    fn test_board_hash_transposition() {
+        use std::collections::HashSet;
+
+        let mut first = Board::new();
+        for r#move in ["Nf3", "Nf6", "Nc3", "Nc6"] {
+            first.make_move(r#move);
+        }
+
+        let mut second = Board::new();
+        for r#move in ["Nc3", "Nc6", "Nf3", "Nf6"] {
+            second.make_move(r#move);
+        }
+
+        let mut positions = HashSet::new();
+        positions.insert(first.clone());
+        positions.insert(second);
+        assert_eq!(positions.len(), 1);
+
+        // castle rights order and uncapturable en passant targets don't matter
+        let board = Board::from_fen("r3k2r/8/8/8/4P3/8/8/R3K2R b KQkq e3 0 1").unwrap();
+        let same = Board::from_fen("r3k2r/8/8/8/4P3/8/8/R3K2R b qkQK - 0 1").unwrap();
+        assert_eq!(board, same);
+        positions.insert(board);
+        positions.insert(same);
+        assert_eq!(positions.len(), 2);
+
+        let mut other_color = first;
+        other_color.active_color = Color::Black;
+        assert!(positions.insert(other_color));
+    }
+
+    #[test]
+    fn test_pieces_of() {
+        let board = Board::new();
+        assert_eq!(board.pieces_of(Color::White).count(), 16);
+        assert!(board
+            .pieces_of(Color::Black)
+            .all(|(square, piece)| piece.color() == &Color::Black && square.0 < 2));
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.pieces_of(Color::Black).collect::<Vec<_>>(),
+            vec![(SquareCoords(0, 4), Piece::King(Color::Black))]
+        );
+    }
+
+    #[test]
+    fn test_move_piece() {
+        let square = |s: &str| SquareCoords::from_san_str(s).unwrap();
+
+        let mut board = Board::new();
+        let r#move = board.move_piece(square("e2"), square("e4"), None).unwrap();
+        assert_eq!(r#move.piece, Some(Piece::Pawn(Color::White)));
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        // not black's piece to move and not a legal destination
+        assert!(board.move_piece(square("d2"), square("d4"), None).is_none());
+        assert!(board.move_piece(square("e7"), square("e4"), None).is_none());
+
+        let mut board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.move_piece(square("a7"), square("a8"), None).is_none());
+
+        let r#move = board
+            .move_piece(
+                square("a7"),
+                square("b8"),
+                Some(Piece::Knight(Color::Black)),
+            )
+            .unwrap();
+        assert_eq!(r#move.promotion, Some(Piece::Knight(Color::White)));
+        assert!(r#move.capture);
+        assert_eq!(board.fen(), "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_legal_moves_cache() {
+        let mut board = Board::new();
+        assert_eq!(board.legal_moves(), board.legal_moves());
+
+        board.make_move("e4");
+        assert_eq!(board.legal_moves().len(), 20);
+        assert!(board.legal_moves().iter().all(|m| m.color == Color::Black));
+
+        // direct changes to the public fields are noticed as well
+        board.squares[1] = [None; 8];
+        assert_eq!(board.legal_moves().len(), 48);
+
+        board.active_color = Color::White;
+        assert!(board.legal_moves().iter().all(|m| m.color == Color::White));
+    }
+
+    #[test]
+    fn test_en_passant_in_legal_moves() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        let en_passant = board
+            .legal_moves()
+            .into_iter()
+            .find(|m| m.to_uci_str() == "e5f6")
+            .unwrap();
+        assert!(en_passant.capture);
+        assert!(!board.legal_moves_uci().contains(&"e5d6".to_string()));
+
+        // the target is set after a double pawn move of either color
+        let mut board = Board::from_fen("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1").unwrap();
+        board.make_move("e4");
+        assert_eq!(board.en_passant_target, SquareCoords::from_san_str("e3"));
+        assert!(board.legal_moves_uci().contains(&"d4e3".to_string()));
+
+        let mut board = Board::from_fen("4k3/4p3/8/3P4/8/8/8/4K3 b - - 0 1").unwrap();
+        board.make_move("e5");
+        assert_eq!(board.en_passant_target, SquareCoords::from_san_str("e6"));
+        assert!(board.legal_moves_uci().contains(&"d5e6".to_string()));
+    }
+
+    #[test]
+    fn test_make_move_capturing() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+
+        // en passant reports the pawn next to the destination square
+        let (r#move, captured) = board.make_move_capturing("exf6").unwrap();
+        assert_eq!(r#move.to_uci_str(), "e5f6");
+        assert_eq!(captured, Some(Piece::Pawn(Color::Black)));
+        assert_eq!(
+            board.get_piece(SquareCoords::from_san_str("f5").unwrap()),
+            None
+        );
+
+        // quiet move
+        let (_, captured) = board.make_move_capturing("Nc6").unwrap();
+        assert_eq!(captured, None);
+
+        // normal capture
+        let (_, captured) = board.make_move_capturing("fxg7").unwrap();
+        assert_eq!(captured, Some(Piece::Pawn(Color::Black)));
+        let (_, captured) = board.make_move_capturing("Bxg7").unwrap();
+        assert_eq!(captured, Some(Piece::Pawn(Color::White)));
+
+        // illegal move
+        assert!(board.make_move_capturing("Qxh7").is_none());
+    }
+
+    #[test]
+    fn test_fullmove_number() {
+        let mut board = Board::new();
+        for (r#move, fullmove_number) in [("e4", 1), ("e5", 2), ("Nf3", 2), ("Nc6", 3)] {
+            board.make_move(r#move);
+            assert_eq!(board.fullmove_number, fullmove_number);
+        }
+        assert_eq!(
+            board.fen(),
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"
+        );
+
+        // starting on black's turn
+        let mut board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        board.make_move("c5");
+        assert_eq!(board.fullmove_number, 2);
+        board.make_move("Nf3");
+        assert_eq!(board.fullmove_number, 2);
+
+        // castling counts as a move as well
+        let mut board = Board::from_fen("r3k3/8/8/8/8/8/8/4K3 b q - 0 30").unwrap();
+        board.make_move("O-O-O");
+        assert_eq!(board.fullmove_number, 31);
+    }
+
+    #[test]
+    fn test_mid_game_fen_counters() {
+        let fen = "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQK2R b KQkq - 5 17";
+
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.active_color, Color::Black);
+        assert_eq!(board.halfmove_clock, 5);
+        assert_eq!(board.fullmove_number, 17);
+
+        board.make_move("d6");
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.fullmove_number, 18);
+
+        let mut board = Board::from_fen(fen).unwrap();
+        board.make_move("O-O");
+        assert_eq!(board.halfmove_clock, 6);
+        assert_eq!(board.fullmove_number, 18);
+
+        board.make_move("Nc3");
+        assert_eq!(board.halfmove_clock, 7);
+        assert_eq!(board.fullmove_number, 18);
+    }
+
+    #[test]
+    fn test_moves_avoiding_draw() {
+        let mut board = Board::new();
+        for r#move in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+            board.make_move(r#move);
+        }
+
+        let moves = board.moves_avoiding_draw();
+        let repeating = Move::from_san("Ng8", &board).unwrap();
+        assert!(!moves.contains(&repeating));
+        assert_eq!(moves.len(), board.legal_moves().len() - 1);
+
+        // every quiet move reaches the fifty move rule, pawn moves and
+        // captures reset the clock
+        let board = Board::from_fen("4k2r/8/8/8/8/8/4P3/4K3 w - - 99 80").unwrap();
+        let moves = board.moves_avoiding_draw();
+        assert_eq!(moves.len(), 2);
+        assert!(moves
+            .iter()
+            .all(|m| m.piece == Some(Piece::Pawn(Color::White))));
+    }
+
+    #[test]
+    fn test_material_signature() {
+        assert_eq!(
+            Board::new().material_signature(),
+            "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP"
+        );
+
+        let board = Board::from_fen("8/8/3k4/8/8/8/8/R3K3 b - - 0 70").unwrap();
+        assert_eq!(board.material_signature(), "KRvK");
+
+        let board = Board::from_fen("8/5p2/3k2n1/8/1B6/2K5/8/7Q w - - 0 50").unwrap();
+        assert_eq!(board.material_signature(), "KQBvKNP");
+    }
+
+    #[test]
+    fn test_move_index() {
+        let board = Board::from_fen("r3k2r/pP3ppp/8/3pP3/8/8/5PPP/R3K2R w KQkq d6 0 1").unwrap();
+
+        for (index, r#move) in board.legal_moves_sorted().iter().enumerate() {
+            assert_eq!(board.move_index(r#move), Some(index));
+            assert_eq!(board.move_by_index(index), Some(*r#move));
+        }
+
+        let en_passant = Move::from_uci("e5d6", &board).unwrap();
+        let index = board.move_index(&en_passant).unwrap();
+        assert_eq!(board.move_by_index(index), Some(en_passant));
+
+        let illegal = Move::from_uci("e1e3", &board).unwrap();
+        assert_eq!(board.move_index(&illegal), None);
+        assert_eq!(board.move_by_index(board.legal_moves().len()), None);
+    }
+
+    #[test]
+    fn test_see() {
+        let see = |fen: &str, r#move: &str| {
+            let board = Board::from_fen(fen).unwrap();
+            board.see(&Move::from_san(r#move, &board).unwrap())
+        };
+
+        // pawn takes a defended pawn
+        assert_eq!(see("4k3/8/2p5/3p4/4P3/8/8/4K3 w - - 0 1", "exd5"), 0);
+
+        // queen takes an undefended pawn
+        assert_eq!(see("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1", "Qxd5"), 1);
+
+        // knight takes a pawn defended by a pawn
+        assert_eq!(see("4k3/8/2p5/3p4/8/4N3/8/4K3 w - - 0 1", "Nxd5"), -2);
+
+        // rooks doubled on the file win the pawn defended by a rook
+        assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "Rxd5"), 1);
+
+        // black stops recapturing when it would lose material
+        assert_eq!(see("3qk3/8/4p3/3n4/8/8/3Q4/3RK3 w - - 0 1", "Qxd5"), -6);
+        assert_eq!(see("3qk3/8/8/3n4/8/8/3Q4/3RK3 w - - 0 1", "Qxd5"), 3);
+
+        // the king can't recapture on a defended square
+        assert_eq!(see("8/8/3k4/3p4/8/8/3R4/3RK3 w - - 0 1", "Rxd5+"), 1);
+
+        // promotion
+        assert_eq!(see("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "bxa8=Q"), 13);
+
+        // quiet moves and castles
+        assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1", "O-O-O"), 0);
+        assert_eq!(see("4k3/8/2p5/8/8/8/8/3RK3 w - - 0 1", "Rd5"), -5);
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        // hanging knight
+        let board = Board::from_fen("rnbqkb1r/pppppppp/8/8/4n3/3P4/PPP1PPPP/RNBQKBNR w KQkq - 0 3")
+            .unwrap();
+        assert!(!board.is_quiet());
+
+        // closed position
+        let board =
+            Board::from_fen("rnbqkbnr/ppp2ppp/4p3/3pP3/3P4/8/PPP2PPP/RNBQKBNR w KQkq - 0 4")
+                .unwrap();
+        assert!(board.is_quiet());
+
+        // check without captures
+        let board = Board::from_fen("4k3/8/8/8/8/8/5PPP/r5K1 w - - 0 1").unwrap();
+        assert!(!board.is_quiet());
+
+        // only an en passant capture
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        assert!(!board.is_quiet());
+    }
+
+    #[test]
+    fn test_to_string_coordinates_all() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        let drawing = board.to_string_coordinates_all();
+        let lines = drawing
+            .lines()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        // files, borders and one line per rank plus the lines between them
+        assert_eq!(lines.len(), 19);
+        assert_eq!(lines[0], lines[18]);
+
+        // every file letter sits right above the center of its squares
+        let rank_8 = &lines[2];
+        let rank_1 = &lines[16];
+        for (i, file) in ('a'..='h').enumerate() {
+            let column = 4 + i * 4;
+            assert_eq!(lines[0][column], file);
+            assert_eq!(rank_8[column - 2], '│');
+            assert_eq!(rank_8[column + 2], '│');
+        }
+        assert_eq!(
+            rank_8[4 + 4 * 4],
+            Piece::King(Color::Black).to_figurine_char()
+        );
+        assert_eq!(rank_1[4], Piece::Rook(Color::White).to_figurine_char());
+
+        // rank numbers on both sides
+        assert_eq!(rank_8.first(), Some(&'8'));
+        assert_eq!(rank_8.last(), Some(&'8'));
+        assert_eq!(rank_1.first(), Some(&'1'));
+        assert_eq!(rank_1.last(), Some(&'1'));
+    }
+
+    #[test]
+    fn test_play_after_game_over() {
+        let mut board = Board::new();
+        for r#move in ["f3", "e5", "g4"] {
+            assert!(board.play(r#move).is_ok());
+        }
+        assert_eq!(board.outcome(), None);
+
+        board.play("Qh4#").unwrap();
+        assert!(board.is_game_over());
+        assert_eq!(board.outcome(), Some(Outcome::Checkmate(Color::Black)));
+
+        let fen = board.fen();
+        assert!(matches!(
+            board.play("a3"),
+            Err(PlayError::GameOver(Outcome::Checkmate(Color::Black)))
+        ));
+        assert_eq!(board.fen(), fen);
+
+        // automatic draws end the game as well
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K1N1 w - - 0 1").unwrap();
+        assert!(matches!(
+            board.play("Nf3"),
+            Err(PlayError::GameOver(Outcome::InsufficientMaterial))
+        ));
+    }
+
+    #[test]
+    fn test_check_escapes() {
+        assert_eq!(Board::new().check_escapes(), None);
+
+        // bishop check that can be answered in every way
+        let board =
+            Board::from_fen("rnbqk1nr/pppp1ppp/8/4p3/1b1P4/P7/1PP1PPPP/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        let escapes = board.check_escapes().unwrap();
+        let uci = |moves: &[Move]| {
+            let mut moves = moves.iter().map(|m| m.to_uci_str()).collect::<Vec<_>>();
+            moves.sort();
+            moves
+        };
+
+        assert_eq!(uci(&escapes.king_moves), Vec::<String>::new());
+        assert_eq!(uci(&escapes.captures_of_checker), vec!["a3b4"]);
+        assert_eq!(
+            uci(&escapes.blocks),
+            vec!["b1c3", "b1d2", "c1d2", "c2c3", "d1d2"]
+        );
+
+        // en passant capture of the checking pawn
+        let board = Board::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1").unwrap();
+        let escapes = board.check_escapes().unwrap();
+        assert_eq!(uci(&escapes.captures_of_checker), vec!["e4d3"]);
+        assert!(escapes.blocks.is_empty());
+        assert_eq!(escapes.king_moves.len(), 8);
+    }
+
+    #[test]
+    fn test_apply_unchecked() {
+        let mut checked = Board::new();
+        let mut unchecked = Board::new();
+
+        for r#move in [
+            "e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+", "Be2", "Bg4", "Nf3", "Bxf3", "O-O",
+        ] {
+            let r#move = checked.make_move(r#move).unwrap();
+            unchecked.apply_unchecked(&r#move);
+
+            assert_eq!(unchecked.fen(), checked.fen());
+            assert_eq!(unchecked.position_history, checked.position_history);
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let board = Board::new();
+        let square = |s: &str| SquareCoords::from_san_str(s).unwrap();
+
+        assert_eq!(
+            board.find(Piece::Rook(Color::White)),
+            vec![square("a1"), square("h1")]
+        );
+        assert_eq!(board.find(Piece::Pawn(Color::Black)).len(), 8);
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.find(Piece::Queen(Color::White)).is_empty());
+    }
+
+    #[test]
+    fn test_fifty_move_rule() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert!(!board.fifty_move_rule());
+        assert!(!board.draw());
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert!(board.fifty_move_rule());
+        assert!(board.draw());
+        assert!(!board.seventyfive_move_rule());
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        board.make_move("Ra2");
+        assert!(board.fifty_move_rule());
+    }
+
+    #[test]
+    fn test_for_search() {
+        let mut board = Board::new();
+
+        for r#move in [
+            "e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7", "Re1", "b5", "Bb3",
+            "d6", "c3", "O-O", "h3", "Nb8", "d4", "Nbd7",
+        ] {
+            board.make_move(r#move).unwrap();
+
+            let search_board = board.for_search();
+            assert_eq!(search_board.position_history, vec![board.fen()]);
+            assert_eq!(search_board.fen(), board.fen());
+            assert_eq!(search_board.legal_moves(), board.legal_moves());
+            assert_eq!(search_board.check(), board.check());
+        }
+    }
+
+    #[test]
+    fn test_occupied() {
+        let board = Board::new();
+        let occupied = board.occupied();
+
+        assert_eq!(occupied, BitBoard(0xffff_0000_0000_ffff));
+        assert_eq!(occupied.iter_squares().count(), 32);
+        assert!(occupied
+            .iter_squares()
+            .all(|square| board.get_piece(square).is_some()));
+    }
+
+    #[test]
+    fn test_goto_initial_position() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let mut board = Board::from_fen(fen).unwrap();
+        board.make_move("Bb5");
+
+        assert_eq!(board.initial_fen(), fen);
+
+        let initial = board.goto(0).unwrap();
+        assert_eq!(initial.fen(), fen);
+        assert_eq!(initial.position_history, vec![fen.to_string()]);
+        assert_eq!(initial, Board::from_fen(fen).unwrap());
+
+        assert_eq!(board.goto(1).unwrap().fen(), board.fen());
+        assert!(board.goto(2).is_none());
+    }
+
+    #[test]
+    fn test_set_en_passant() {
+        // black just played c7-c5
+        let mut board = Board::from_fen("4k3/8/8/2pP4/8/8/8/4K3 w - - 0 2").unwrap();
+        board.set_en_passant(Some("c6")).unwrap();
+
+        assert_eq!(board.fen(), "4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 2");
+        assert_eq!(board.position_history, vec![board.fen()]);
+        assert!(board.legal_moves_uci().contains(&"d5c6".to_string()));
+
+        // wrong rank for the side to move, no pawn to capture, bad square
+        let mut board = Board::from_fen("4k3/8/8/2pP4/8/8/8/4K3 w - - 0 2").unwrap();
+        for target in ["c3", "e6", "c9"] {
+            assert!(matches!(
+                board.set_en_passant(Some(target)),
+                Err(FenParseError::EnPassant)
+            ));
+        }
+        assert_eq!(board.en_passant_target, None);
+
+        // white just played e2-e4
+        let mut board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert!(board.set_en_passant(Some("e3")).is_ok());
+        assert!(board.set_en_passant(Some("e6")).is_err());
+        assert_eq!(board.en_passant_target, SquareCoords::from_san_str("e3"));
+    }
+
+    #[test]
+    fn test_render() {
+        let board = Board::new();
+
+        let unicode = board.render(RenderStyle::Unicode);
+        assert_eq!(unicode, board.to_string());
+        assert_eq!(unicode.lines().count(), 18);
+        assert_eq!(
+            unicode.lines().nth(15),
+            Some("│ ♜ │ ♞ │ ♝ │ ♛ │ ♚ │ ♝ │ ♞ │ ♜ │ 1")
+        );
+
+        let ascii = board.render(RenderStyle::AsciiLetters);
+        assert!(ascii.is_ascii());
+        assert_eq!(
+            ascii.lines().next(),
+            Some("+---+---+---+---+---+---+---+---+")
+        );
+        assert_eq!(
+            ascii.lines().nth(7),
+            Some("|   |   |   |   |   |   |   |   | 5")
+        );
+        assert_eq!(
+            ascii.lines().nth(15),
+            Some("| R | N | B | Q | K | B | N | R | 1")
+        );
+        assert_eq!(
+            ascii.lines().last(),
+            Some("  a   b   c   d   e   f   g   h ")
+        );
+
+        let mixed = board.render(RenderStyle::UnicodePiecesAsciiGrid);
+        assert_eq!(
+            mixed.lines().next(),
+            Some("+---+---+---+---+---+---+---+---+")
+        );
+        assert_eq!(
+            mixed.lines().nth(1),
+            Some("| ♖ | ♘ | ♗ | ♕ | ♔ | ♗ | ♘ | ♖ | 8")
+        );
+    }
+
+    #[test]
+    fn test_play_random_game() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let (board, moves, outcome) = Board::play_random_game(&mut StdRng::seed_from_u64(42));
+
+        assert_eq!(board.outcome(), Some(outcome));
+        // the seventy-five-move rule bounds the length of any game
+        assert!(moves.len() < 6000);
+
+        let mut replay = Board::new();
+        for r#move in &moves {
+            assert!(replay.push(r#move));
+        }
+        assert_eq!(replay.fen(), board.fen());
+
+        // the same seed plays the same game
+        let (_, same_moves, same_outcome) = Board::play_random_game(&mut StdRng::seed_from_u64(42));
+        assert_eq!(same_moves, moves);
+        assert_eq!(same_outcome, outcome);
+    }
+
+    #[test]
+    fn test_positions_equal_ignoring_counters() {
+        let mut first = Board::new();
+        for r#move in ["e4", "e5", "Nf3"] {
+            first.make_move(r#move);
+        }
+
+        let mut second = Board::new();
+        for r#move in ["Nf3", "e5", "e4"] {
+            second.make_move(r#move);
+        }
+
+        assert!(first.positions_equal_ignoring_counters(&second));
+
+        // e3 is the en passant target only after the second move order, but
+        // no black pawn can capture on it
+        let first = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+        let second = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 7").unwrap();
+        assert!(first.positions_equal_ignoring_counters(&second));
+
+        let capturable = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let not_capturable = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert!(!capturable.positions_equal_ignoring_counters(&not_capturable));
+
+        let mut other_castle_rights = Board::new();
+        other_castle_rights.castle_rights.pop();
+        assert!(!Board::new().positions_equal_ignoring_counters(&other_castle_rights));
+    }
+
+    #[test]
+    fn test_push_uci() {
+        let mut board = Board::new();
+
+        assert_eq!(
+            board.push_uci("e2e4"),
+            Some("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".to_string())
+        );
+        assert_eq!(board.fen(), board.position_history[1]);
+
+        let fen = board.fen();
+        for uci in ["e2e4", "e7e4", "e1g1", "xyz", ""] {
+            assert_eq!(board.push_uci(uci), None);
+            assert_eq!(board.fen(), fen);
+        }
+        assert_eq!(board.position_history.len(), 2);
+    }
+
+    #[test]
+    fn test_is_legal_san_and_uci() {
+        let board = Board::new();
+
+        for san in ["e4", "Nf3", "a3"] {
+            assert!(board.is_legal_san(san), "{}", san);
+        }
+        for san in ["e5", "Ke2", "O-O", "Qxd7", "nonsense", ""] {
+            assert!(!board.is_legal_san(san), "{}", san);
+        }
+
+        assert!(board.is_legal_uci("e2e4"));
+        assert!(!board.is_legal_uci("e1e2"));
+        assert!(!board.is_legal_uci("e7e5"));
+
+        // the board is left untouched
+        assert_eq!(board.fen(), Board::new().fen());
+        assert_eq!(board.position_history.len(), 1);
+    }
+
+    #[test]
+    fn test_is_dead_position() {
+        let dead = |fen: &str| Board::from_fen(fen).unwrap().is_dead_position();
+
+        // two light-squared bishops against a light-squared bishop
+        assert!(dead("4k3/8/8/8/2b5/8/8/1B1K1B2 w - - 0 1"));
+        // one of the bishops on a dark square
+        assert!(!dead("4k3/8/8/8/3b4/8/8/1B1K1B2 w - - 0 1"));
+        assert!(!dead("4k3/8/8/8/2b5/8/8/1B1KB3 w - - 0 1"));
+
+        assert!(dead("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert!(!dead("4k3/8/8/8/8/8/8/4KQ2 w - - 0 1"));
+    }
+
+    #[test]
+    fn test_legal_moves_ordered() {
+        // the queen on d5 can be taken by the pawn and the knight, the rook
+        // on h8 by the promoting pawn and the knight on a2 by the knight and
+        // the rook
+        let board = Board::from_fen("4k2r/6P1/8/3q4/4P3/2N5/n7/R3K3 w - - 0 1").unwrap();
+        let ordered = board.legal_moves_ordered();
+
+        assert_eq!(ordered.len(), board.legal_moves().len());
+        assert_eq!(
+            ordered[..8]
+                .iter()
+                .map(Move::to_uci_str)
+                .collect::<Vec<_>>(),
+            ["e4d5", "c3d5", "g7h8q", "g7h8r", "g7h8b", "g7h8n", "c3a2", "a1a2"]
+        );
+        assert!(ordered[..8].iter().all(|m| m.capture));
+
+        let promotions = ordered[8..]
+            .iter()
+            .take_while(|m| m.promotion.is_some())
+            .map(Move::to_uci_str)
+            .collect::<Vec<_>>();
+        assert_eq!(promotions, ["g7g8q", "g7g8r", "g7g8b", "g7g8n"]);
+
+        assert!(ordered[12..]
+            .iter()
+            .all(|m| !m.capture && m.promotion.is_none()));
+    }
+
+    #[test]
+    fn test_legal_moves_san_pairs() {
+        let board =
+            Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/5N2/PPP2PPP/RNBQKB1R w KQkq - 0 4")
+                .unwrap();
+        let pairs = board.legal_moves_san_pairs();
+
+        assert_eq!(pairs.len(), board.legal_moves().len());
+        for (r#move, san) in &pairs {
+            assert_eq!(san, &board.san_of(r#move));
+        }
+
+        let sans = pairs.into_iter().map(|(_, san)| san).collect::<Vec<_>>();
+        assert!(sans.contains(&"Nbd2".to_string()));
+        assert!(sans.contains(&"Nfd2".to_string()));
+        assert!(!sans.contains(&"Nd2".to_string()));
+        assert_eq!(sans, board.legal_moves_san());
+    }
+
+    #[test]
+    fn test_make_san_move_localized() {
+        let nf3 = Move::from_san("Nf3", &Board::new());
+
+        let mut board = Board::new();
+        assert_eq!(
+            board.make_san_move_localized("Sf3", SanLanguage::German),
+            nf3
+        );
+        let mut board = Board::new();
+        assert_eq!(
+            board.make_san_move_localized("Cf3", SanLanguage::French),
+            nf3
+        );
+
+        // French "R" is the king, so "Re2" only works once e2 is free
+        let mut board = Board::new();
+        board.make_move("e4");
+        board.make_move("e5");
+        let r#move = board.make_san_move_localized("Re2", SanLanguage::French);
+        assert_eq!(
+            r#move.and_then(|m| m.piece),
+            Some(Piece::King(Color::White))
+        );
+        assert_eq!(board.position_history.len(), 4);
+    }
+
+    #[test]
+    fn test_threat_map() {
+        let board = Board::new();
+
+        // every square of the 2nd and 3rd ranks and the 1st rank squares
+        // covered by the rooks, the queen and the king
+        let expected = ["b1", "c1", "d1", "e1", "f1", "g1"]
+            .iter()
+            .map(|s| s.to_string())
+            .chain("abcdefgh".chars().map(|file| format!("{}2", file)))
+            .chain("abcdefgh".chars().map(|file| format!("{}3", file)))
+            .map(|s| SquareCoords::from_san_str(&s).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(board.threat_map(Color::White), expected);
+
+        // pins are ignored: the pinned knight still attacks its squares
+        let board = Board::from_fen("4k3/8/8/b7/8/8/3N4/4K3 w - - 0 1").unwrap();
+        let threats = board.threat_map(Color::White);
+        for square in ["b1", "b3", "c4", "e4", "f3", "f1"] {
+            assert!(threats.contains(&SquareCoords::from_san_str(square).unwrap()));
+        }
+    }
 
-            // handle promotion
-            if let Some(promotion_piece) = r#move.promotion {
-                self.set_piece(dst_square, Some(promotion_piece));
-            } else {
-                self.set_piece(dst_square, r#move.piece);
-            }
+    #[test]
+    fn test_san_move_map() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            // three queens that can reach b2 need full disambiguation
+            "6k1/8/8/8/8/Q7/8/Q1Q4K w - - 0 1",
+            "r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let moves = board.san_move_map();
 
-            self.set_piece(src_square, None);
+            assert_eq!(moves.len(), board.legal_moves().len(), "{}", fen);
+            for (san, r#move) in &moves {
+                assert_eq!(
+                    Move::from_san(san, &board).as_ref(),
+                    Some(r#move),
+                    "{}",
+                    san
+                );
+            }
         }
 
-        self.update_castle_rights(r#move);
-        self.position_history.push(self.fen());
-        self.en_passant_target = self.update_en_passant_target_square(r#move);
-        self.active_color = self.active_color.invert();
-        self.fullmove_number += match self.active_color {
-            Color::White => 1,
-            Color::Black => 0,
-        };
+        let board = Board::from_fen("6k1/8/8/8/8/Q7/8/Q1Q4K w - - 0 1").unwrap();
+        let moves = board.san_move_map();
+        for san in ["Qa1b2", "Q3b2", "Qcb2"] {
+            assert!(moves.contains_key(san), "{}", san);
+        }
     }
 
-    /// Returns if a given move will leave the king in check.
-    /// The move passed to this method is assumed to be legal and valid,
-    /// otherwise undefined behavior may occur.
-    pub(crate) fn future_check(&self, r#move: &Move) -> bool {
-        let mut cloned_board = self.clone();
-        cloned_board.apply_move(r#move);
-        cloned_board.active_color = cloned_board.active_color.invert();
-        cloned_board.check()
+    #[test]
+    fn test_material_hash() {
+        let hash = |fen: &str| Board::from_fen(fen).unwrap().material_hash();
+
+        // 8 pawns, 2 knights, 2 bishops, 2 rooks and a queen per side
+        let side = 8 | 2 << 4 | 2 << 7 | 2 << 10 | 1 << 13;
+        assert_eq!(Board::new().material_hash(), side | side << 16);
+
+        // same material, different placement
+        assert_eq!(
+            hash("4k3/2p5/8/8/8/8/3P4/R3K3 w - - 0 1"),
+            hash("3k4/7p/8/8/4P3/8/8/4K2R b - - 0 1")
+        );
+
+        // different material or the same material on the other side
+        assert_ne!(
+            hash("4k3/2p5/8/8/8/8/3P4/R3K3 w - - 0 1"),
+            hash("4k3/2p5/8/8/8/8/3P4/B3K3 w - - 0 1")
+        );
+        assert_ne!(
+            hash("4k3/2p5/8/8/8/8/3P4/R3K3 w - - 0 1"),
+            hash("r3k3/2p5/8/8/8/8/3P4/4K3 w - - 0 1")
+        );
     }
 
-    /// Returns the pieces an its respectives square coordinates from where a
-    /// given square is being attacked.
-    pub(crate) fn square_attackers(&self, src_square: SquareCoords) -> Vec<(Piece, SquareCoords)> {
-        let mut attacking_pieces = Vec::new();
-        let color = self.active_color.invert();
+    #[test]
+    fn test_parse_uci_pv() {
+        let board = Board::new();
 
-        let pieces = [
-            Piece::Pawn(color),
-            Piece::Knight(color),
-            Piece::Bishop(color),
-            Piece::Rook(color),
-            Piece::Queen(color),
-            Piece::King(color),
-        ];
+        let pv = board.parse_uci_pv("e2e4 e7e5 g1f3").unwrap();
+        assert_eq!(
+            pv.iter().map(Move::to_uci_str).collect::<Vec<_>>(),
+            ["e2e4", "e7e5", "g1f3"]
+        );
+        assert_eq!(
+            board.san_line(&["e2e4", "e7e5", "g1f3"]).unwrap(),
+            "1. e4 e5 2. Nf3"
+        );
 
-        // starting from the square we are checking, iterate through all the directions
-        // of each piece and check if there are any pieces attacking the square.
-        for piece in &pieces {
-            for direction in &piece.directions() {
-                // pawns can only attack diagonally
-                if piece == &Piece::Pawn(color) && direction.1 == 0 {
-                    continue;
-                }
+        // stops at the illegal move and at garbage
+        assert_eq!(board.parse_uci_pv("d2d4 d7d5 d4d5 c2c4").unwrap().len(), 2);
+        assert_eq!(board.parse_uci_pv("  g1f3   xx g8f6").unwrap().len(), 1);
 
-                let mut src_square = match piece {
-                    // since in this method we are going from the square we are checking to the
-                    // src_square, we need to invert the direction if the piece is a pawn.
-                    Piece::Pawn(_) => SquareCoords(
-                        (src_square.0 as i8 - direction.0) as usize,
-                        (src_square.1 as i8 + direction.1) as usize,
-                    ),
-                    _ => src_square + direction,
-                };
+        assert_eq!(board.parse_uci_pv("e2e5"), None);
+        assert_eq!(board.parse_uci_pv(""), None);
+        assert_eq!(board.fen(), Board::new().fen());
+    }
 
-                while src_square.inside_board() {
-                    let src_square_piece = self.get_piece(src_square);
-                    if src_square_piece.is_some_and(|p| &p != piece) {
-                        break;
-                    }
+    #[test]
+    fn test_future_check_pinned_pieces() {
+        // the previous implementation, flipping the active color back
+        let old_future_check = |board: &Board, r#move: &Move| {
+            let mut cloned_board = board.clone();
+            cloned_board.apply_move(r#move);
+            cloned_board.active_color = cloned_board.active_color.invert();
+            cloned_board.check()
+        };
 
-                    if src_square_piece.is_some() {
-                        attacking_pieces.push((*piece, src_square));
-                    }
+        for fen in [
+            "4q2k/8/8/b7/8/8/3NR3/rNB1K3 w - - 0 1",
+            "4k3/4r3/8/8/1b6/8/3P4/Q3K2r w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/K2pP2r/8/8/8/8 w - d6 0 2",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
 
-                    src_square += direction;
-                    match piece {
-                        Piece::Queen(_) => continue,
-                        Piece::Rook(_) => continue,
-                        Piece::Bishop(_) => continue,
-                        Piece::Knight(_) => break,
-                        Piece::King(_) => break,
-                        Piece::Pawn(_) => break,
-                    }
+            for (src_square, piece) in board.pieces_of(board.active_color) {
+                for dst_square in board.all_piece_moves(src_square) {
+                    let mut r#move = Move {
+                        piece: Some(piece),
+                        color: board.active_color,
+                        src_square: Some(src_square),
+                        dst_square: Some(dst_square),
+                        promotion: None,
+                        castle: None,
+                        capture: false,
+                    };
+                    r#move.capture = board.capture_square(&r#move).is_some();
+
+                    assert_eq!(
+                        board.future_check(&r#move),
+                        old_future_check(&board, &r#move),
+                        "{} {}",
+                        fen,
+                        r#move.to_uci_str()
+                    );
                 }
             }
         }
 
-        attacking_pieces
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+        assert!(!board.is_king_attacked(Color::White));
+        assert!(!board.is_king_attacked(Color::Black));
     }
 
-    /// Castles kingside for the given active color.
-    /// This method assumes that the castle is legal.
-    fn castle_kingside(&mut self) {
-        let row = match self.active_color {
-            Color::White => 7,
-            Color::Black => 0,
-        };
+    #[test]
+    fn test_goto_lenient_board() {
+        let fen = "4k3/pppppppp/p7/8/8/8/PPPPPPPP/4K3 w - - 0 1";
+        let mut board = Board::from_fen_lenient(fen).unwrap();
+        board.make_move("Kd1").unwrap();
 
-        let king_square = (row, 4).into();
-        let rook_square = (row, 7).into();
-        let new_king_square = (row, 6).into();
-        let new_rook_square = (row, 5).into();
+        assert_eq!(board.goto(0).unwrap().fen(), fen);
+        assert_eq!(board.goto(1).unwrap().fen(), board.fen());
+    }
 
-        self.set_piece(king_square, None);
-        self.set_piece(rook_square, None);
-        self.set_piece(new_king_square, Some(Piece::King(self.active_color)));
-        self.set_piece(new_rook_square, Some(Piece::Rook(self.active_color)));
+    #[test]
+    fn test_validate_material() {
+        // nine white pawns
+        let fen = "4k3/8/8/8/8/P7/PPPPPPPP/4K3 w - - 0 1";
+        assert!(matches!(
+            Board::from_fen(fen),
+            Err(FenParseError::Material(BoardError::TooManyPawns(
+                Color::White
+            )))
+        ));
+
+        let board = Board::from_fen_lenient(fen).unwrap();
+        assert_eq!(
+            board.validate_material(),
+            Err(BoardError::TooManyPawns(Color::White))
+        );
+
+        // seventeen black pieces, with no more than 8 pawns
+        let fen = "rnbqkbnr/pppppppp/n7/8/8/8/8/4K3 w - - 0 1";
+        assert!(matches!(
+            Board::from_fen(fen),
+            Err(FenParseError::Material(BoardError::TooManyPieces(
+                Color::Black
+            )))
+        ));
+
+        assert_eq!(Board::new().validate_material(), Ok(()));
     }
 
-    /// Castles queenside for the current active color.
-    /// This method assumes that the castle is legal.
-    fn castle_queenside(&mut self) {
-        let row = match self.active_color {
-            Color::White => 7,
-            Color::Black => 0,
-        };
+    #[test]
+    fn test_game_phase() {
+        let phase = |fen: &str| Board::from_fen(fen).unwrap().game_phase();
 
-        let king_square = (row, 4).into();
-        let rook_square = (row, 0).into();
-        let new_king_square = (row, 2).into();
-        let new_rook_square = (row, 3).into();
+        assert_eq!(Board::new().game_phase(), 24);
+        assert_eq!(phase("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"), 0);
+        assert_eq!(phase("r3k3/8/8/8/8/8/8/2B1K1N1 w - - 0 1"), 4);
 
-        self.set_piece(king_square, None);
-        self.set_piece(rook_square, None);
-        self.set_piece(new_king_square, Some(Piece::King(self.active_color)));
-        self.set_piece(new_rook_square, Some(Piece::Rook(self.active_color)));
+        // promoted queens can't push the phase over 24
+        assert_eq!(phase("rnbqkbnr/QQ6/8/8/8/8/8/RNBQKBNR w - - 0 1"), 24);
     }
 
-    /// Checks if en passant is possible in next turn given a move.
-    fn update_en_passant_target_square(&self, r#move: &Move) -> Option<SquareCoords> {
-        if let (Some(src_square), Some(dst_square)) = (r#move.src_square, r#move.dst_square) {
-            // if the move is not a double pawn move, return false
-            if r#move.piece != Some(Piece::Pawn(self.active_color))
-                || (dst_square.0 as i8 - src_square.0 as i8).abs() != 2
-            {
-                return None;
-            }
+    #[test]
+    fn test_san_of_full_square_disambiguation() {
+        // the queens on h1 and e4 can also reach e1; h1 shares the file of h4
+        // and e4 its rank, so only the full square tells them apart
+        let mut board = Board::from_fen("8/8/k7/8/4Q2Q/8/8/2K4Q w - - 0 1").unwrap();
 
-            let en_passant_target: SquareCoords = {
-                match self.active_color {
-                    Color::Black => (dst_square.0 - 1, dst_square.1).into(),
-                    Color::White => (dst_square.0 + 1, dst_square.1).into(),
-                }
-            };
+        let r#move = Move::from_san("Qh4e1", &board).unwrap();
+        assert_eq!(r#move.to_uci_str(), "h4e1");
+        assert!(board.legal_moves().contains(&r#move));
+        assert_eq!(board.san_of(&r#move), "Qh4e1");
 
-            for direction in &PAWN_CAPTURE_DIRECTIONS {
-                let src_square = en_passant_target + direction;
+        for uci in ["h1e1", "e4e1"] {
+            let r#move = Move::from_uci(uci, &board).unwrap();
+            let san = board.san_of(&r#move);
 
-                if !src_square.inside_board() {
-                    continue;
-                }
+            assert_eq!(Move::from_san(&san, &board), Some(r#move), "{}", san);
+        }
+        assert_eq!(board.make_move("Qh4e1"), Some(r#move));
+    }
 
-                if self.get_piece(src_square) == Some(Piece::Pawn(self.active_color.invert())) {
-                    return Some(en_passant_target);
-                }
-            }
+    #[test]
+    fn test_set_fen() {
+        let mut board = Board::new();
+        board.make_move("e4").unwrap();
+        board.make_move("e5").unwrap();
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            board.set_fen(fen).unwrap();
+
+            assert_eq!(board.fen(), fen);
+            assert_eq!(board, Board::from_fen(fen).unwrap());
+            assert_eq!(board.position_history, vec![fen.to_string()]);
+            assert_eq!(
+                board.legal_moves(),
+                Board::from_fen(fen).unwrap().legal_moves()
+            );
         }
 
-        None
+        // an invalid FEN string leaves the board as it was
+        let fen = board.fen();
+        assert!(board.set_fen("4k3/8/8/8/8/8/8/4K3 w -").is_err());
+        assert!(board.set_fen("4k3/8/8/8/8/8/8/4K3 w X - 0 1").is_err());
+        assert!(board
+            .set_fen("4k3/pppppppp/p7/8/8/8/8/4K3 w - - 0 1")
+            .is_err());
+        assert_eq!(board.fen(), fen);
     }
 
-    /// Returns the square of the current active color king.
-    fn king_square(&self) -> SquareCoords {
-        for (row, &col) in self.squares.iter().enumerate() {
-            for (col, &piece) in col.iter().enumerate() {
-                if piece == Some(Piece::King(self.active_color)) {
-                    return SquareCoords(row, col);
-                }
-            }
-        }
+    #[test]
+    fn test_set_fen_keeps_capacity() {
+        let mut board = Board::new();
+        board.castle_rights.reserve(8);
+        board.position_history.reserve(32);
 
-        unreachable!("King can't be missing from the battle!")
+        let castle_rights_capacity = board.castle_rights.capacity();
+        let history_capacity = board.position_history.capacity();
+
+        for fen in [
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        ] {
+            board.set_fen(fen).unwrap();
+
+            assert_eq!(board.castle_rights.capacity(), castle_rights_capacity);
+            assert_eq!(board.position_history.capacity(), history_capacity);
+        }
     }
 
-    /// Updates the castle rights given a move.
-    fn update_castle_rights(&mut self, r#move: &Move) {
-        // castling move
-        if r#move.castle.is_some() {
-            match self.active_color {
-                Color::White => self.castle_rights.retain(|x| {
-                    x != &CastleRights::WhiteKingside && x != &CastleRights::WhiteQueenside
-                }),
-                Color::Black => self.castle_rights.retain(|x| {
-                    x != &CastleRights::BlackKingside && x != &CastleRights::BlackQueenside
-                }),
-            }
+    #[test]
+    fn test_move_vector() {
+        let board = Board::from_fen("4k3/8/8/8/8/2N5/8/R3K2R w KQ - 0 1").unwrap();
+
+        // the knight on c3 moves two squares in one direction and one in the other
+        for r#move in board.legal_moves().iter().filter(|m| m.is_knight_move()) {
+            let (row, col) = board.move_vector(r#move).unwrap();
+            assert!(matches!((row.abs(), col.abs()), (2, 1) | (1, 2)));
         }
 
-        // white king moves
-        if r#move.piece.is_some_and(|p| p == Piece::King(Color::White)) {
-            self.castle_rights.retain(|x| {
-                x != &CastleRights::WhiteKingside && x != &CastleRights::WhiteQueenside
-            });
+        let r#move = board.parse_legal_move("Nd5").unwrap();
+        assert_eq!(board.move_vector(&r#move), Some((-2, 1)));
+        let r#move = board.parse_legal_move("Na4").unwrap();
+        assert_eq!(board.move_vector(&r#move), Some((-1, -2)));
+
+        // the rook on a1 moves along the a-file
+        let r#move = board.parse_legal_move("Ra6").unwrap();
+        assert!(!r#move.is_knight_move());
+        assert_eq!(board.move_vector(&r#move), Some((-5, 0)));
+
+        let r#move = board.parse_legal_move("O-O").unwrap();
+        assert!(!r#move.is_knight_move());
+        assert_eq!(board.move_vector(&r#move), None);
+    }
+
+    #[test]
+    fn test_auto_draw_claim() {
+        let mut board = Board::new();
+        assert_eq!(board.auto_draw_claim(), None);
+
+        for r#move in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+            board.make_move(r#move).unwrap();
+            assert_eq!(board.auto_draw_claim(), None);
         }
 
-        // black king moves
-        if r#move.piece.is_some_and(|p| p == Piece::King(Color::Black)) {
-            self.castle_rights.retain(|x| {
-                x != &CastleRights::BlackKingside && x != &CastleRights::BlackQueenside
-            });
+        board.make_move("Ng8").unwrap();
+        assert_eq!(board.auto_draw_claim(), Some(Outcome::ThreefoldRepetition));
+
+        // checkmate ends the game even when the fifty moves are reached
+        let board =
+            Board::from_fen("rnb1kbnr/pppp1ppp/4p3/8/5PPq/8/PPPPP2P/RNBQKBNR w KQkq - 100 60")
+                .unwrap();
+        assert_eq!(board.auto_draw_claim(), None);
+    }
+
+    #[test]
+    fn test_can_reach() {
+        let square = |s| SquareCoords::from_san_str(s).unwrap();
+        let board = Board::new();
+
+        for s in ["a3", "a4", "c3", "f3", "h3", "e4"] {
+            assert!(board.can_reach(square(s)), "{}", s);
+        }
+        // e2 is taken by a pawn of the same color and e5 is too far for it
+        for s in ["e2", "e5", "d5", "e8"] {
+            assert!(!board.can_reach(square(s)), "{}", s);
         }
 
-        // white kingside rook moves or is captured
-        if r#move.src_square.is_some_and(|s| s == (7, 7))
-            || r#move.dst_square.is_some_and(|s| s == (7, 7))
-        {
-            self.castle_rights
-                .retain(|x| x != &CastleRights::WhiteKingside);
+        // castling lands the king on g1 and c1, which the rooks reach too
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert!(board.can_reach(square("g1")));
+        assert!(board.can_reach(square("c1")));
+        assert!(!board.can_reach(square("b2")));
+
+        // the bishop on e2 is pinned and can't go to d3
+        let board = Board::from_fen("4r1k1/8/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        assert!(!board.can_reach(square("d3")));
+        assert!(board.can_reach(square("d2")));
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut board = Board::new();
+        board.enable_undo();
+
+        for r#move in ["e4", "e5", "Nf3"] {
+            board.make_move(r#move).unwrap();
         }
 
-        // white queenside rook moves or is captured
-        if r#move.src_square.is_some_and(|s| s == (7, 0))
-            || r#move.dst_square.is_some_and(|s| s == (7, 0))
-        {
-            self.castle_rights
-                .retain(|x| x != &CastleRights::WhiteQueenside);
+        for _ in 0..3 {
+            assert!(board.undo());
         }
+        assert_eq!(board.fen(), FEN_STARTING_POSITION);
+        assert_eq!(board.position_history, vec![FEN_STARTING_POSITION]);
+        assert!(!board.undo());
 
-        // black kingside rook moves or is captured
-        if r#move.src_square.is_some_and(|s| s == (0, 7))
-            || r#move.dst_square.is_some_and(|s| s == (0, 7))
-        {
-            self.castle_rights
-                .retain(|x| x != &CastleRights::BlackKingside);
+        // castles, en passant and promotions with a capture
+        let fen = "r3k2r/1P6/8/8/3pP3/8/8/R3K2R b KQkq e3 4 30";
+        let mut board = Board::from_fen(fen).unwrap();
+        board.enable_undo();
+        for r#move in ["dxe3", "O-O-O", "O-O", "bxa8=Q", "Rxa8"] {
+            board.make_move(r#move).unwrap();
         }
+        for _ in 0..5 {
+            assert!(board.undo());
+        }
+        assert_eq!(board.fen(), fen);
+        assert_eq!(
+            board.legal_moves(),
+            Board::from_fen(fen).unwrap().legal_moves()
+        );
+    }
 
-        // black queenside rook moves or is captured
-        if r#move.src_square.is_some_and(|s| s == (0, 0))
-            || r#move.dst_square.is_some_and(|s| s == (0, 0))
-        {
-            self.castle_rights
-                .retain(|x| x != &CastleRights::BlackQueenside);
+    #[test]
+    fn test_undo_disabled() {
+        let mut board = Board::new();
+        board.make_move("e4").unwrap();
+        assert!(!board.undo());
+        assert!(board.undo_stack.is_none());
+
+        // copies for a search never record their moves
+        board.enable_undo();
+        let mut search_board = board.for_search();
+        search_board.make_move("e5").unwrap();
+        assert!(search_board.undo_stack.is_none());
+
+        board.make_move("e5").unwrap();
+        assert!(board.undo());
+        assert!(!board.undo());
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_undo_derived_boards() {
+        let mut board = Board::new();
+        board.enable_undo();
+        board.make_move("e4").unwrap();
+
+        let mut flipped = board.flip_vertical();
+        let fen = flipped.fen();
+        assert!(!flipped.undo());
+        assert_eq!(flipped.fen(), fen);
+        assert_eq!(flipped.initial_fen(), fen);
+
+        assert!(!board.flip_horizontal().undo());
+        assert!(!board.with_active_color(Color::White).undo());
+        assert!(!board.for_search().undo());
+
+        // the history keeps its first entry even if it was shortened by hand
+        board.position_history.truncate(1);
+        assert!(board.undo());
+        assert_eq!(board.fen(), FEN_STARTING_POSITION);
+        assert_eq!(board.position_history.len(), 1);
+    }
+
+    #[test]
+    fn test_goto_shortened_history() {
+        let mut board = Board::new();
+        board.make_move("e4").unwrap();
+
+        // a board for a search starts its history at its current position
+        let mut search_board = board.for_search();
+        search_board.make_move("e5").unwrap();
+        assert_eq!(search_board.initial_fen(), board.fen());
+        assert_eq!(search_board.goto(0).unwrap().fen(), board.fen());
+        assert!(search_board.goto(2).is_none());
+
+        // moves recorded for undo before the ply can still be taken back
+        board.enable_undo();
+        for r#move in ["e5", "Nf3", "Nc6"] {
+            board.make_move(r#move).unwrap();
         }
+
+        let mut previous = board.goto(3).unwrap();
+        assert!(previous.undo());
+        assert!(previous.undo());
+        assert!(!previous.undo());
+        assert_eq!(previous.fen(), board.goto(1).unwrap().fen());
+
+        let mut previous = board.goto(1).unwrap();
+        assert!(!previous.undo());
     }
-}
 
-impl std::fmt::Display for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let fisrt_line = "┌───┬───┬───┬───┬───┬───┬───┬───┐";
-        let last_line = "└───┴───┴───┴───┴───┴───┴───┴───┘";
-        let horizontal_line = "├───┼───┼───┼───┼───┼───┼───┼───┤";
-        let rows = ['8', '7', '6', '5', '4', '3', '2', '1'];
-        let cols = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+    #[test]
+    fn test_undo_random_games() {
+        use rand::{rngs::StdRng, SeedableRng};
 
-        writeln!(f, "{}", fisrt_line)?;
+        let mut rng = StdRng::seed_from_u64(94);
 
-        for (i, &row) in self.squares.iter().enumerate() {
-            write!(f, "│")?;
-            for (j, &piece) in row.iter().enumerate() {
-                if j == 7 {
-                    match piece {
-                        Some(piece) => write!(f, " {} │ {}", piece, rows[i]),
-                        None => write!(f, "   │ {}", rows[i]),
-                    }?;
-                } else {
-                    match piece {
-                        Some(piece) => write!(f, " {} │", piece),
-                        None => write!(f, "   │"),
-                    }?;
-                }
+        for _ in 0..10 {
+            let (_, moves, _) = Board::play_random_game(&mut rng);
+            let mut board = Board::new();
+            board.enable_undo();
+            for r#move in &moves {
+                board.apply_unchecked(r#move);
             }
+            let history = board.position_history.clone();
 
-            if i != 7 {
-                writeln!(f, "\n{}", horizontal_line)?;
-            } else {
-                writeln!(f, "\n{}", last_line)?;
+            // every position of the game is restored in turn
+            for fen in history.iter().rev().skip(1) {
+                assert!(board.undo());
+                assert_eq!(&board.fen(), fen);
             }
+            assert!(!board.undo());
         }
+    }
 
-        for col in &cols {
-            write!(f, "  {} ", col)?;
+    #[test]
+    fn test_legal_moves_of_kind() {
+        let board = Board::new();
+
+        let knight_moves = board.legal_moves_of_kind(PieceKind::Knight);
+        assert_eq!(knight_moves.len(), 4);
+        assert!(knight_moves
+            .iter()
+            .all(|m| m.piece == Some(Piece::Knight(Color::White))));
+
+        assert!(board.legal_moves_of_kind(PieceKind::Bishop).is_empty());
+        assert_eq!(board.legal_moves_of_kind(PieceKind::Pawn).len(), 16);
+
+        // castles count as king moves
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert_eq!(board.legal_moves_of_kind(PieceKind::King).len(), 6);
+        assert_eq!(board.legal_moves_of_kind(PieceKind::Rook).len(), 9);
+    }
+
+    #[test]
+    fn test_fen_with() {
+        let mut board = Board::new();
+        for r#move in [
+            "e4", "c5", "Nf3", "d6", "d4", "cxd4", "Nxd4", "Nf6", "Nc3", "a6",
+        ] {
+            board.make_move(r#move).unwrap();
         }
 
-        Ok(())
+        let fen = "rnbqkb1r/1p2pppp/p2p1n2/8/3NP3/2N5/PPP2PPP/R1BQKB1R w KQkq - 0 6";
+        assert_eq!(board.fen_with(true), fen);
+        assert_eq!(board.fen_with(true), board.fen());
+        assert_eq!(
+            board.fen_with(false),
+            "rnbqkb1r/1p2pppp/p2p1n2/8/3NP3/2N5/PPP2PPP/R1BQKB1R w KQkq -"
+        );
+
+        // the short form can be read back, with the counters defaulted
+        let parsed = Board::from_fen(&board.fen_with(false)).unwrap();
+        assert_eq!(parsed, board);
+        assert_eq!(parsed.fen_with(false), board.fen_with(false));
     }
-}
 
-impl Default for Board {
-    fn default() -> Self {
-        Board::new()
+    #[test]
+    fn test_is_promotion() {
+        let square = |s| SquareCoords::from_san_str(s).unwrap();
+        let board = Board::from_fen("1r2k3/P7/8/8/8/8/4P2p/4K3 w - - 0 1").unwrap();
+
+        assert!(board.is_promotion(square("a7"), square("a8")));
+        assert!(board.is_promotion(square("a7"), square("b8")));
+        assert!(!board.is_promotion(square("e2"), square("e3")));
+        assert!(!board.is_promotion(square("e2"), square("e4")));
+        // the king is not a pawn and the pawn on h2 is black
+        assert!(!board.is_promotion(square("e1"), square("e8")));
+        assert!(!board.is_promotion(square("h2"), square("h1")));
+
+        let board = board.with_active_color(Color::Black);
+        assert!(board.is_promotion(square("h2"), square("h1")));
+        assert!(!board.is_promotion(square("a7"), square("a8")));
+    }
+
+    #[test]
+    fn test_make_san_move_localized_mixed_letters() {
+        let mut board = Board::new();
+
+        for r#move in ["e4", "e5"] {
+            board.make_move(r#move).unwrap();
+        }
+
+        // English letters are not read in German or French
+        assert_eq!(
+            board.make_san_move_localized("Bc4", SanLanguage::German),
+            None
+        );
+        assert_eq!(
+            board.make_san_move_localized("Nf3", SanLanguage::French),
+            None
+        );
+        assert_eq!(board.position_history.len(), 3);
+
+        assert!(board
+            .make_san_move_localized("Lc4", SanLanguage::German)
+            .is_some());
+        assert!(board
+            .make_san_move_localized("Cf6", SanLanguage::French)
+            .is_some());
+        assert_eq!(
+            board.fen(),
+            "rnbqkb1r/pppp1ppp/5n2/4p3/2B1P3/8/PPPP1PPP/RNBQK1NR w KQkq - 2 3"
+        );
+    }
+
+    #[test]
+    fn test_san_of_discovered_check() {
+        // moving the e4 bishop uncovers a check from the e1 rook, the b3 bishop
+        // can also move to d5 so the move must be disambiguated by file
+        let board = Board::from_fen("4k3/8/8/8/4B3/1B6/8/K3R3 w - - 0 1").unwrap();
+
+        let r#move = Move::from_uci("e4d5", &board).unwrap();
+        assert!(board.gives_check(&r#move));
+        assert_eq!(board.san_of(&r#move), "Bed5+");
+
+        let r#move = Move::from_uci("b3d5", &board).unwrap();
+        assert!(!board.gives_check(&r#move));
+        assert_eq!(board.san_of(&r#move), "Bbd5");
+
+        // discovered checkmate
+        let board = Board::from_fen("R2B3k/6pp/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let r#move = Move::from_uci("d8f6", &board).unwrap();
+        assert_eq!(board.san_of(&r#move), "Bf6#");
     }
 }
@@ -79,4 +79,12 @@ impl CastleRights {
             CastleRights::BlackQueenside => 'q',
         }
     }
+
+    /// Returns the color of the player the castle right belongs to.
+    pub fn color(&self) -> Color {
+        match self {
+            CastleRights::WhiteKingside | CastleRights::WhiteQueenside => Color::White,
+            CastleRights::BlackKingside | CastleRights::BlackQueenside => Color::Black,
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use std::fmt;
 
 /// Represents the color of a piece or a player.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Color {
     White,
     Black,
@@ -16,6 +16,26 @@ impl Color {
         }
     }
 
+    /// Returns the index of the color, to be used with per color arrays.
+    /// White -> 0
+    /// Black -> 1
+    pub fn index(self) -> usize {
+        match self {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
+
+    /// Tries to create a color from the given index, the inverse of
+    /// [Color::index].
+    pub fn from_index(index: usize) -> Option<Color> {
+        match index {
+            0 => Some(Color::White),
+            1 => Some(Color::Black),
+            _ => None,
+        }
+    }
+
     /// Inverts the color.
     /// White -> Black
     /// Black -> White
@@ -35,3 +55,19 @@ impl fmt::Display for Color {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_color_index() {
+        for color in [Color::White, Color::Black] {
+            assert_eq!(Color::from_index(color.index()), Some(color));
+        }
+
+        assert_eq!(Color::White.index(), 0);
+        assert_eq!(Color::Black.index(), 1);
+        assert_eq!(Color::from_index(2), None);
+    }
+}
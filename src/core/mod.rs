@@ -1,14 +1,18 @@
+pub mod bitboard;
 pub mod board;
 pub mod castle;
 pub mod color;
 pub mod r#move;
 mod movegen;
+pub mod outcome;
 pub mod piece;
 pub mod square_coords;
 
-pub use board::Board;
+pub use bitboard::BitBoard;
+pub use board::{Board, BoardError, CheckEscapes, PlayError, RenderStyle};
 pub use castle::{CastleKind, CastleRights};
 pub use color::Color;
-pub use piece::Piece;
-pub use r#move::Move;
+pub use outcome::{NoMovesReason, Outcome};
+pub use piece::{Piece, PieceKind};
+pub use r#move::{Move, SanLanguage, SanOptions};
 pub use square_coords::SquareCoords;
@@ -28,8 +28,63 @@ pub struct Move {
     pub capture: bool,
 }
 
+/// Options for the SAN generated by
+/// [Move::to_san_with_options()](crate::Move::to_san_with_options()).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SanOptions {
+    /// Appends " e.p." to en passant captures (e.g. "exd6 e.p.").
+    pub en_passant_suffix: bool,
+}
+
+/// Languages of the piece letters used in SAN, see
+/// [Board::make_san_move_localized()](crate::Board::make_san_move_localized()).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum SanLanguage {
+    /// K, Q, R, B, N.
+    #[default]
+    English,
+    /// K (König), D (Dame), T (Turm), L (Läufer), S (Springer).
+    German,
+    /// R (Roi), D (Dame), T (Tour), F (Fou), C (Cavalier).
+    French,
+}
+
+impl SanLanguage {
+    /// Returns the English SAN letter of the given piece letter of the
+    /// language, or `None` if the letter doesn't name a piece in it.
+    pub fn to_english(&self, c: char) -> Option<char> {
+        let letters = match self {
+            SanLanguage::English => ['K', 'Q', 'R', 'B', 'N'],
+            SanLanguage::German => ['K', 'D', 'T', 'L', 'S'],
+            SanLanguage::French => ['R', 'D', 'T', 'F', 'C'],
+        };
+
+        letters
+            .iter()
+            .position(|&letter| letter == c)
+            .map(|i| ['K', 'Q', 'R', 'B', 'N'][i])
+    }
+
+    /// Translates the piece letters of a SAN string written in the language
+    /// to English. Only uppercase letters name pieces, files are lowercase
+    /// and the castle notation is the same in every language. Returns `None`
+    /// if an uppercase letter other than the `O` of castles doesn't name a
+    /// piece in the language, so letters of another language, which may name
+    /// a different piece, are not read by mistake.
+    pub fn translate(&self, san: &str) -> Option<String> {
+        san.chars()
+            .map(|c| match c {
+                'O' => Some(c),
+                c if c.is_ascii_uppercase() => self.to_english(c),
+                c => Some(c),
+            })
+            .collect()
+    }
+}
+
 impl Move {
-    /// Returns an UCI representation of the move.
+    /// Returns an UCI representation of the move, as expected by the UCI
+    /// protocol (e.g. "e2e4", "e7e8q" or "e1g1" for castling).
     pub fn to_uci_str(&self) -> String {
         if let Some(castle) = self.castle {
             return castle.to_uci_str(&self.color);
@@ -44,7 +99,44 @@ impl Move {
             None => "".to_string(),
         };
 
-        format!("{}-{}{}", src_square, dst_square, promotion)
+        format!("{}{}{}", src_square, dst_square, promotion)
+    }
+
+    /// Returns an UCI representation of the move with the source and
+    /// destination squares separated by a '-' (e.g. "e2-e4").
+    pub fn to_uci_dashed(&self) -> String {
+        let uci = self.to_uci_str();
+        format!("{}-{}", &uci[0..2], &uci[2..])
+    }
+
+    /// Returns the SAN of the move in the position of the given board, like
+    /// [Board::san_of()](crate::Board::san_of()), formatted according to the
+    /// given options. The move is assumed to be legal in that position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move, SanOptions};
+    ///
+    /// let board =
+    ///     Board::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3").unwrap();
+    /// let r#move = Move::from_uci("e5d6", &board).unwrap();
+    /// let options = SanOptions { en_passant_suffix: true };
+    ///
+    /// assert_eq!(r#move.to_san_with_options(&board, options), "exd6 e.p.");
+    /// ```
+    pub fn to_san_with_options(&self, board: &Board, options: SanOptions) -> String {
+        let mut san = board.san_of(self);
+
+        // en passant is the only capture that doesn't take the piece on the
+        // destination square
+        let en_passant = self.capture && board.capture_square(self) != self.dst_square;
+
+        if options.en_passant_suffix && en_passant {
+            san.push_str(" e.p.");
+        }
+
+        san
     }
 
     /// Returns a SAN representation of the move.
@@ -83,23 +175,23 @@ impl Move {
     /// notation.
     ///
     /// Either an UCI move with or without '-' will be accepted
-    /// (e.g. "e2e4" or "e2-e4").
+    /// (e.g. "e2e4" or "e2-e4"). The promotion piece may be written in
+    /// uppercase and preceded by '=', so "e7e8q", "e7e8Q" and "e7e8=Q" are
+    /// all accepted.
     pub fn from_uci(uci_str: &str, board: &Board) -> Option<Move> {
         let re = Regex::new(UCI_MOVE_REGEX).expect("Invalid UCI move regex");
         let re_dash = Regex::new(UCI_MOVE_DASH_REGEX).expect("Invalid UCI move dash regex");
 
-        let dash_uci = re_dash.is_match(uci_str);
-        if !re.is_match(uci_str) && !dash_uci {
-            return None;
-        }
-
-        let (src_square_str, dst_square_str, promotion_char) = match dash_uci {
-            true => (&uci_str[0..2], &uci_str[3..5], uci_str.chars().nth(5)),
-            false => (&uci_str[0..2], &uci_str[2..4], uci_str.chars().nth(4)),
-        };
+        let captures = re.captures(uci_str).or_else(|| re_dash.captures(uci_str))?;
+        let src_square_str = format!("{}{}", &captures[1], &captures[2]);
+        let dst_square_str = format!("{}{}", &captures[3], &captures[4]);
+        let promotion_char = captures
+            .get(5)
+            .and_then(|m| m.as_str().chars().next())
+            .map(|c| c.to_ascii_lowercase());
 
-        let src_square = SquareCoords::from_san_str(src_square_str)?;
-        let dst_square = SquareCoords::from_san_str(dst_square_str)?;
+        let src_square = SquareCoords::from_san_str(&src_square_str)?;
+        let dst_square = SquareCoords::from_san_str(&dst_square_str)?;
         let castle = CastleKind::from_uci_str(uci_str);
         let promotion = match promotion_char {
             Some(char) => Some(Piece::from_uci_char(char, board.active_color)?),
@@ -116,27 +208,43 @@ impl Move {
                 promotion: None,
                 capture: false,
             }),
-            None => Some(Move {
-                piece: board.get_piece(src_square),
-                color: board.active_color,
-                src_square: Some(src_square),
-                dst_square: Some(dst_square),
-                castle: None,
-                promotion,
-                capture: board.get_piece(dst_square).is_some(),
-            }),
+            None => {
+                let piece = board.get_piece(src_square);
+                let en_passant = piece == Some(Piece::Pawn(board.active_color))
+                    && board.en_passant_target == Some(dst_square);
+
+                Some(Move {
+                    piece,
+                    color: board.active_color,
+                    src_square: Some(src_square),
+                    dst_square: Some(dst_square),
+                    castle: None,
+                    promotion,
+                    capture: board.get_piece(dst_square).is_some() || en_passant,
+                })
+            }
         }
     }
 
     /// Returns a [Move] struct representation of the given move in standard
     /// algebraic notation. Will return a move when it is valid even if it
     /// is illegal.
+    ///
+    /// Trailing check and checkmate markers are ignored, including the "++"
+    /// some sources use for double check (e.g. "Qd5+", "Qd5++" and "Qd5#" are
+    /// all parsed as "Qd5"). So is the "e.p." annotation of en passant
+    /// captures (e.g. "exd6 e.p.").
     pub fn from_san(r#move: &str, board: &Board) -> Option<Move> {
+        let r#move = match r#move.trim_end().strip_suffix("e.p.") {
+            Some(r#move) => r#move.trim_end(),
+            None => r#move,
+        };
+
         // castling
         let re = Regex::new(CASTLE_REGEX).expect("Invalid castle regex");
 
         if re.is_match(r#move) {
-            let castle_type = CastleKind::from_san_str(r#move)?;
+            let castle_type = CastleKind::from_san_str(r#move.trim_end_matches(['+', '#']))?;
             return Some(Move {
                 piece: None,
                 color: board.active_color,
@@ -349,6 +457,173 @@ impl Move {
 
         None
     }
+
+    /// Packs the move into 16 bits: the index of the source square (a1 = 0,
+    /// h8 = 63) in the 6 lowest bits, the index of the destination square in
+    /// the next 6 bits and a flag in the 4 highest bits. Castles are stored
+    /// as the squares the king moves from and to.
+    ///
+    /// The flags are: 0 for quiet moves, 1 for double pawn pushes, 2 and 3
+    /// for kingside and queenside castles and 4 for captures. Promotions add
+    /// 8 plus the promotion piece (0 knight, 1 bishop, 2 rook, 3 queen), and
+    /// promotion captures add the capture flag as well. En passant captures
+    /// are stored as captures, as a move alone doesn't tell them apart; the
+    /// board passed to [from_u16()](crate::Move::from_u16()) does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board = Board::new();
+    /// let r#move = Move::from_san("Nf3", &board).unwrap();
+    ///
+    /// assert_eq!(r#move.to_u16(), 6 | 21 << 6);
+    /// assert_eq!(Move::from_u16(r#move.to_u16(), &board), Some(r#move));
+    /// ```
+    pub fn to_u16(&self) -> u16 {
+        let (src_square, dst_square) = self.squares();
+
+        let flag = match (self.castle, self.promotion) {
+            (Some(CastleKind::Kingside), _) => MOVE_FLAG_KINGSIDE_CASTLE,
+            (Some(CastleKind::Queenside), _) => MOVE_FLAG_QUEENSIDE_CASTLE,
+            (None, Some(promotion)) => {
+                let piece = match promotion {
+                    Piece::Bishop(_) => 1,
+                    Piece::Rook(_) => 2,
+                    Piece::Queen(_) => 3,
+                    _ => 0,
+                };
+                let capture = match self.capture {
+                    true => MOVE_FLAG_CAPTURE,
+                    false => 0,
+                };
+
+                MOVE_FLAG_PROMOTION | capture | piece
+            }
+            (None, None) if self.capture => MOVE_FLAG_CAPTURE,
+            (None, None)
+                if self.piece == Some(Piece::Pawn(self.color))
+                    && src_square.0.abs_diff(dst_square.0) == 2 =>
+            {
+                MOVE_FLAG_DOUBLE_PAWN_PUSH
+            }
+            (None, None) => MOVE_FLAG_QUIET,
+        };
+
+        src_square.index() as u16 | (dst_square.index() as u16) << 6 | flag << 12
+    }
+
+    /// Unpacks a move packed by [to_u16()](crate::Move::to_u16()). The board
+    /// must be the position the move was played in, since the moving piece
+    /// is read from it. Returns `None` if there is no piece of the active
+    /// color on the source square or the flag is not valid. The move is not
+    /// checked to be legal.
+    pub fn from_u16(packed: u16, board: &Board) -> Option<Move> {
+        let src_square = SquareCoords::from_index((packed & 0x3f) as usize)?;
+        let dst_square = SquareCoords::from_index((packed >> 6 & 0x3f) as usize)?;
+        let flag = packed >> 12;
+        let color = board.active_color;
+
+        let castle = match flag {
+            MOVE_FLAG_KINGSIDE_CASTLE => Some(CastleKind::Kingside),
+            MOVE_FLAG_QUEENSIDE_CASTLE => Some(CastleKind::Queenside),
+            _ => None,
+        };
+
+        if castle.is_some() {
+            return Some(Move {
+                piece: None,
+                color,
+                src_square: None,
+                dst_square: None,
+                castle,
+                promotion: None,
+                capture: false,
+            });
+        }
+
+        let piece = board
+            .get_piece(src_square)
+            .filter(|p| p.color() == &color)?;
+
+        let promotion = match flag & MOVE_FLAG_PROMOTION {
+            0 if flag > MOVE_FLAG_CAPTURE => return None,
+            0 => None,
+            _ => Some(match flag & 3 {
+                0 => Piece::Knight(color),
+                1 => Piece::Bishop(color),
+                2 => Piece::Rook(color),
+                _ => Piece::Queen(color),
+            }),
+        };
+
+        Some(Move {
+            piece: Some(piece),
+            color,
+            src_square: Some(src_square),
+            dst_square: Some(dst_square),
+            castle: None,
+            promotion,
+            capture: flag & MOVE_FLAG_CAPTURE != 0,
+        })
+    }
+
+    /// Returns whether the move is made by a knight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chessr::{Board, Move};
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert!(Move::from_san("Nf3", &board).unwrap().is_knight_move());
+    /// assert!(!Move::from_san("e4", &board).unwrap().is_knight_move());
+    /// ```
+    pub fn is_knight_move(&self) -> bool {
+        matches!(self.piece, Some(Piece::Knight(_)))
+    }
+
+    /// Returns the source and destination squares of the move. For castles,
+    /// these are the squares the king moves from and to.
+    pub(crate) fn squares(&self) -> (SquareCoords, SquareCoords) {
+        match self.castle {
+            Some(castle) => {
+                let row = match self.color {
+                    Color::White => 7,
+                    Color::Black => 0,
+                };
+                let dst_col = match castle {
+                    CastleKind::Kingside => 6,
+                    CastleKind::Queenside => 2,
+                };
+                (SquareCoords(row, 4), SquareCoords(row, dst_col))
+            }
+            // if the move is not a castle, it must have a source and destination
+            // square so we can unwrap safely
+            None => (self.src_square.unwrap(), self.dst_square.unwrap()),
+        }
+    }
+
+    /// Returns a key that gives a stable total ordering of the moves in a
+    /// position: by source square, then destination square, then promotion
+    /// piece. Squares are indexed from a1 to h8 and castles are keyed by the
+    /// squares the king moves from and to.
+    pub(crate) fn sort_key(&self) -> (usize, usize, usize) {
+        let (src_square, dst_square) = self.squares();
+
+        let promotion = match self.promotion {
+            None => 0,
+            Some(Piece::Queen(_)) => 1,
+            Some(Piece::Rook(_)) => 2,
+            Some(Piece::Bishop(_)) => 3,
+            Some(Piece::Knight(_)) => 4,
+            Some(_) => 5,
+        };
+
+        (src_square.index(), dst_square.index(), promotion)
+    }
 }
 
 /// Returns a move from algebraic notation data.
@@ -406,7 +681,9 @@ fn algebraic_piece_move(
                     Piece::Bishop(_) => continue,
                     Piece::Knight(_) => break,
                     Piece::King(_) => break,
-                    Piece::Pawn(_) => unreachable!(),
+                    // pawns are handled by algebraic_pawn_move, bail out
+                    // instead of panicking if one ever ends up here
+                    Piece::Pawn(_) => return None,
                 }
             }
 
@@ -494,6 +771,148 @@ fn algebraic_pawn_move(
 mod test {
     use super::*;
 
+    #[test]
+    fn test_to_san_with_options() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        let with_suffix = SanOptions {
+            en_passant_suffix: true,
+        };
+
+        let en_passant = Move::from_uci("e5f6", &board).unwrap();
+        assert_eq!(
+            en_passant.to_san_with_options(&board, SanOptions::default()),
+            "exf6"
+        );
+        assert_eq!(
+            en_passant.to_san_with_options(&board, with_suffix),
+            "exf6 e.p."
+        );
+
+        // other captures and quiet moves are not affected
+        let board = Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2")
+            .unwrap();
+        let capture = Move::from_uci("e4d5", &board).unwrap();
+        assert_eq!(capture.to_san_with_options(&board, with_suffix), "exd5");
+        let quiet = Move::from_uci("e4e5", &board).unwrap();
+        assert_eq!(quiet.to_san_with_options(&board, with_suffix), "e5");
+    }
+
+    #[test]
+    fn test_en_passant_representations_agree() {
+        for (fen, san, uci, captured) in [
+            (
+                "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+                "exd6",
+                "e5d6",
+                "d5",
+            ),
+            ("4k3/8/8/6Pp/8/8/8/4K3 w - h6 0 2", "gxh6", "g5h6", "h5"),
+            ("4k3/8/8/pP6/8/8/8/4K3 w - a6 0 2", "bxa6", "b5a6", "a5"),
+            ("4k3/8/8/8/3Pp3/8/8/4K3 b - d3 0 1", "exd3", "e4d3", "d4"),
+            ("4k3/8/8/8/1pP5/8/8/4K3 b - c3 0 1", "bxc3", "b4c3", "c4"),
+            // the pawn that double pushed gives check and is taken
+            ("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", "exd3", "e4d3", "d4"),
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let from_san = Move::from_san(san, &board).unwrap();
+            let from_uci = Move::from_uci(uci, &board).unwrap();
+            let legal = board
+                .legal_moves()
+                .into_iter()
+                .find(|m| m.to_uci_str() == uci)
+                .unwrap();
+
+            assert_eq!(from_san, from_uci, "{}", fen);
+            assert_eq!(from_uci, legal, "{}", fen);
+            assert!(legal.capture, "{}", fen);
+            assert_eq!(
+                board.capture_square(&legal),
+                SquareCoords::from_san_str(captured),
+                "{}",
+                fen
+            );
+            assert_eq!(board.san_of(&legal).trim_end_matches('+'), san);
+        }
+
+        // the capture would expose the king along the rank: both notations
+        // still read the same move, which is not legal
+        let board = Board::from_fen("8/8/8/K2pP2r/8/8/8/4k3 w - d6 0 2").unwrap();
+        let from_san = Move::from_san("exd6", &board).unwrap();
+        assert_eq!(Some(from_san), Move::from_uci("e5d6", &board));
+        assert!(from_san.capture);
+        assert!(!board.legal_moves().contains(&from_san));
+    }
+
+    #[test]
+    fn test_move_from_san_en_passant_suffix() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        let en_passant = Move::from_uci("e5f6", &board);
+
+        assert!(en_passant.is_some());
+        assert_eq!(Move::from_san("exf6 e.p.", &board), en_passant);
+        assert_eq!(Move::from_san("exf6e.p.", &board), en_passant);
+        assert_eq!(Move::from_san("exf6", &board), en_passant);
+    }
+
+    #[test]
+    fn test_move_u16_round_trip() {
+        let board = Board::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+
+        for (uci, packed) in [
+            // normal move, en passant, castles, promotions
+            ("a1a7", 48 << 6),
+            ("e5d6", 36 | 43 << 6 | 4 << 12),
+            ("e1g1", 4 | 6 << 6 | 2 << 12),
+            ("e1c1", 4 | 2 << 6 | 3 << 12),
+            ("b7b8q", 49 | 57 << 6 | 11 << 12),
+            ("b7b8n", 49 | 57 << 6 | 8 << 12),
+            ("b7a8r", 49 | 56 << 6 | 14 << 12),
+        ] {
+            let r#move = Move::from_uci(uci, &board).unwrap();
+            assert!(board.legal_moves().contains(&r#move), "{}", uci);
+
+            assert_eq!(r#move.to_u16(), packed, "{}", uci);
+            assert_eq!(Move::from_u16(packed, &board), Some(r#move), "{}", uci);
+        }
+
+        let board = Board::new();
+        let r#move = Move::from_san("e4", &board).unwrap();
+        assert_eq!(r#move.to_u16() >> 12, MOVE_FLAG_DOUBLE_PAWN_PUSH);
+        assert_eq!(Move::from_u16(r#move.to_u16(), &board), Some(r#move));
+
+        // empty source square, opponent's piece, unused flag
+        assert_eq!(Move::from_u16(20 | 28 << 6, &board), None);
+        assert_eq!(Move::from_u16(52 | 36 << 6, &board), None);
+        assert_eq!(Move::from_u16(12 | 28 << 6 | 6 << 12, &board), None);
+    }
+
+    #[test]
+    fn test_san_language_translate() {
+        assert_eq!(SanLanguage::German.translate("Sf3").unwrap(), "Nf3");
+        assert_eq!(SanLanguage::German.translate("Lxe5+").unwrap(), "Bxe5+");
+        assert_eq!(SanLanguage::German.translate("e8=D#").unwrap(), "e8=Q#");
+        assert_eq!(SanLanguage::German.translate("Tad1").unwrap(), "Rad1");
+        assert_eq!(SanLanguage::French.translate("Cf3").unwrap(), "Nf3");
+        assert_eq!(SanLanguage::French.translate("Re2").unwrap(), "Ke2");
+        assert_eq!(SanLanguage::French.translate("Fb5").unwrap(), "Bb5");
+        assert_eq!(SanLanguage::French.translate("O-O-O").unwrap(), "O-O-O");
+        assert_eq!(SanLanguage::English.translate("Rxe2").unwrap(), "Rxe2");
+
+        // letters of another language are rejected
+        assert_eq!(SanLanguage::German.translate("Bc4"), None);
+        assert_eq!(SanLanguage::German.translate("e8=Q"), None);
+        assert_eq!(SanLanguage::French.translate("Nf3"), None);
+        assert_eq!(SanLanguage::French.translate("Bb5"), None);
+        assert_eq!(SanLanguage::English.translate("Sf3"), None);
+
+        assert_eq!(SanLanguage::German.to_english('B'), None);
+        assert_eq!(SanLanguage::French.to_english('N'), None);
+    }
+
     #[test]
     fn test_move_from_uci_notation() {
         // normal pawn move
@@ -562,4 +981,128 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn test_move_from_san_check_markers() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3QK2R w K - 0 1").unwrap();
+        let expected = Some(Move {
+            piece: Some(Piece::Queen(Color::White)),
+            color: Color::White,
+            src_square: Some(SquareCoords(7, 3)),
+            dst_square: Some(SquareCoords(3, 3)),
+            promotion: None,
+            castle: None,
+            capture: false,
+        });
+
+        assert_eq!(Move::from_san("Qd5", &board), expected);
+        assert_eq!(Move::from_san("Qd5+", &board), expected);
+        assert_eq!(Move::from_san("Qd5++", &board), expected);
+        assert_eq!(Move::from_san("Qd5#", &board), expected);
+        assert_eq!(Move::from_san("Qd5+++", &board), None);
+        assert_eq!(Move::from_san("Qd5#+", &board), None);
+
+        // castle with check markers
+        for castle in ["O-O", "O-O+", "O-O++", "O-O#"] {
+            assert_eq!(
+                Move::from_san(castle, &board).and_then(|m| m.castle),
+                Some(CastleKind::Kingside)
+            );
+        }
+    }
+
+    #[test]
+    fn test_algebraic_piece_move_pawn() {
+        let board = Board::new();
+        let expected = Some(Move {
+            piece: Some(Piece::Pawn(Color::White)),
+            color: Color::White,
+            src_square: Some(SquareCoords(6, 4)),
+            dst_square: Some(SquareCoords(4, 4)),
+            promotion: None,
+            castle: None,
+            capture: false,
+        });
+
+        assert_eq!(
+            algebraic_piece_move(
+                &Piece::Pawn(Color::White),
+                SquareCoords(4, 4),
+                None,
+                None,
+                &board
+            ),
+            expected
+        );
+
+        // no pawn can reach the square
+        assert_eq!(
+            algebraic_piece_move(
+                &Piece::Pawn(Color::White),
+                SquareCoords(3, 4),
+                None,
+                None,
+                &board
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_move_from_uci_promotion_case() {
+        let board =
+            Board::from_fen("r1bq2nr/1pp1Pppp/p1np2k1/2b5/2B5/3N4/PPPP1PPP/RNBQK2R w KQ - 0 9")
+                .unwrap();
+        let expected = Some(Move {
+            piece: Some(Piece::Pawn(Color::White)),
+            color: Color::White,
+            src_square: Some(SquareCoords(1, 4)),
+            dst_square: Some(SquareCoords(0, 4)),
+            promotion: Some(Piece::Queen(Color::White)),
+            castle: None,
+            capture: false,
+        });
+
+        assert_eq!(Move::from_uci("e7e8q", &board), expected);
+        assert_eq!(Move::from_uci("e7e8Q", &board), expected);
+        assert_eq!(Move::from_uci("e7e8=Q", &board), expected);
+        assert_eq!(Move::from_uci("e7-e8Q", &board), expected);
+        assert_eq!(Move::from_uci("e7e8=", &board), None);
+        assert_eq!(Move::from_uci("e7e8K", &board), None);
+    }
+
+    #[test]
+    fn test_move_to_uci() {
+        let board = Board::new();
+        let r#move = Move::from_san("e4", &board).unwrap();
+        assert_eq!(r#move.to_uci_str(), "e2e4");
+        assert_eq!(r#move.to_uci_dashed(), "e2-e4");
+
+        // castling
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(Move::from_san("O-O", &board).unwrap().to_uci_str(), "e1g1");
+        assert_eq!(
+            Move::from_san("O-O-O", &board).unwrap().to_uci_str(),
+            "e1c1"
+        );
+        assert_eq!(
+            Move::from_san("O-O", &board).unwrap().to_uci_dashed(),
+            "e1-g1"
+        );
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        assert_eq!(Move::from_san("O-O", &board).unwrap().to_uci_str(), "e8g8");
+        assert_eq!(
+            Move::from_san("O-O-O", &board).unwrap().to_uci_str(),
+            "e8c8"
+        );
+
+        // promotion
+        let board =
+            Board::from_fen("r1bq2nr/1pp1Pppp/p1np2k1/2b5/2B5/3N4/PPPP1PPP/RNBQK2R w KQ - 0 9")
+                .unwrap();
+        let r#move = Move::from_uci("e7e8q", &board).unwrap();
+        assert_eq!(r#move.to_uci_str(), "e7e8q");
+        assert_eq!(r#move.to_uci_dashed(), "e7-e8q");
+    }
 }
@@ -1,38 +1,355 @@
+use crate::constants::{BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
 use crate::core::{Board, CastleKind, CastleRights, Color, Move, Piece, SquareCoords};
 
+/// Tells which moves of the active color can be legal and which of them have
+/// to be played out on a copy of the board to know whether they leave their
+/// own king in check. The checking pieces are found once per position and
+/// decide how moves are filtered:
+///
+/// - no checkers: every move is legal except for some moves of pinned pieces
+///   and en passant captures.
+/// - one checker: moves other than the king's must capture the checker or
+///   block its line to the king.
+/// - two checkers: only the king can move.
+struct Legality {
+    /// Number of pieces giving check to the king of the active color.
+    checkers: usize,
+
+    /// Squares the pieces other than the king must move to when the king is
+    /// in check by a single piece: the square of the checker and, for
+    /// sliding pieces, the squares between it and the king.
+    check_mask: [[bool; 8]; 8],
+
+    /// Squares of the pieces of the active color pinned to their king.
+    pinned: [[bool; 8]; 8],
+}
+
+impl Legality {
+    /// Finds the checkers and the pinned pieces of the active color.
+    fn new(board: &Board) -> Legality {
+        let mut legality = Legality {
+            checkers: 0,
+            check_mask: [[false; 8]; 8],
+            pinned: [[false; 8]; 8],
+        };
+
+        let king_square = match board.find(Piece::King(board.active_color)).first() {
+            Some(&square) => square,
+            None => return legality,
+        };
+
+        let checkers = board.square_attackers(king_square);
+        legality.checkers = checkers.len();
+
+        if let [(checker, checker_square)] = checkers[..] {
+            legality.check_mask[checker_square.0][checker_square.1] = true;
+
+            // a sliding checker can also be blocked on the squares between
+            if matches!(checker, Piece::Bishop(_) | Piece::Rook(_) | Piece::Queen(_)) {
+                let direction = (
+                    (checker_square.0 as i8 - king_square.0 as i8).signum(),
+                    (checker_square.1 as i8 - king_square.1 as i8).signum(),
+                );
+
+                let mut square = king_square + direction;
+                while square != checker_square {
+                    legality.check_mask[square.0][square.1] = true;
+                    square += direction;
+                }
+            }
+        }
+
+        // walk away from the king in every sliding direction: a piece of the
+        // active color followed by an enemy slider moving along that line is
+        // pinned
+        for (directions, diagonal) in [(ROOK_DIRECTIONS, false), (BISHOP_DIRECTIONS, true)] {
+            for direction in &directions {
+                let mut square = king_square + direction;
+                let mut candidate = None;
+
+                while square.inside_board() {
+                    if let Some(piece) = board.get_piece(square) {
+                        if piece.color() == &board.active_color {
+                            if candidate.is_some() {
+                                break;
+                            }
+
+                            candidate = Some(square);
+                        } else {
+                            let pins = match piece {
+                                Piece::Queen(_) => true,
+                                Piece::Rook(_) => !diagonal,
+                                Piece::Bishop(_) => diagonal,
+                                _ => false,
+                            };
+
+                            if let (true, Some(pinned)) = (pins, candidate) {
+                                legality.pinned[pinned.0][pinned.1] = true;
+                            }
+
+                            break;
+                        }
+                    }
+
+                    square += direction;
+                }
+            }
+        }
+
+        legality
+    }
+
+    /// Forces every move to be validated by playing it out.
+    #[cfg(test)]
+    fn validate_all() -> Legality {
+        Legality {
+            checkers: 0,
+            check_mask: [[false; 8]; 8],
+            pinned: [[true; 8]; 8],
+        }
+    }
+
+    /// Returns true if only the king can move, which is the case in double
+    /// check.
+    fn king_moves_only(&self) -> bool {
+        self.checkers > 1
+    }
+
+    /// Returns true if the given pseudo-legal move of a piece other than the
+    /// king doesn't leave the king of the active color in check. Only moves
+    /// of pinned pieces and en passant captures, which remove two pieces
+    /// from the same rank, are played out; the rest are decided by the
+    /// checkers alone.
+    fn is_legal(&self, board: &Board, r#move: &Move) -> bool {
+        let en_passant = r#move.piece == Some(Piece::Pawn(board.active_color))
+            && r#move.dst_square.is_some()
+            && r#move.dst_square == board.en_passant_target;
+
+        // en passant can capture a checking pawn without landing on its square
+        let resolves_check = match (self.checkers, r#move.dst_square) {
+            (0, _) => true,
+            (1, Some(dst_square)) => en_passant || self.check_mask[dst_square.0][dst_square.1],
+            _ => false,
+        };
+
+        if !resolves_check {
+            return false;
+        }
+
+        let pinned = r#move
+            .src_square
+            .is_some_and(|square| self.pinned[square.0][square.1]);
+
+        if pinned || en_passant {
+            return !board.future_check(r#move);
+        }
+
+        true
+    }
+}
+
 /// Returns a vec of [Move] containing all possible legal moves in the current
 /// position.
 pub(crate) fn generate_legal_moves(board: &Board) -> Vec<Move> {
+    generate_moves(board, &Legality::new(board))
+}
+
+/// Returns the legal moves of the current position, validating the moves
+/// the given [Legality] asks for.
+fn generate_moves(board: &Board, legality: &Legality) -> Vec<Move> {
     let mut legal_moves = Vec::new();
 
     // piece moves
-    for (row, &col) in board.squares.iter().enumerate() {
-        for (col, &piece) in col.iter().enumerate() {
-            if piece.is_some_and(|p| p.color() != &board.active_color) || piece.is_none() {
-                continue;
-            }
-
-            let mut legal_piece_moves =
-                legal_piece_moves(&piece.unwrap(), (row, col).into(), board);
-            legal_moves.append(&mut legal_piece_moves);
+    for (square, piece) in board.pieces_of(board.active_color) {
+        if legality.king_moves_only() && piece != Piece::King(board.active_color) {
+            continue;
         }
+
+        let mut legal_piece_moves = legal_piece_moves(&piece, square, board, legality);
+        legal_moves.append(&mut legal_piece_moves);
     }
 
     // castle moves
     let mut legal_castle_moves = legal_castle_moves(board);
     legal_moves.append(&mut legal_castle_moves);
 
+    debug_assert!(
+        !has_duplicates(&legal_moves),
+        "duplicate legal moves generated"
+    );
+
     legal_moves
 }
 
+/// Returns true if any legal move of the active color lands on the given
+/// square, generating the moves of one piece at a time and stopping at the
+/// first one found. Castles land on the king's destination square, but they
+/// don't need to be generated: a legal castle needs the squares between the
+/// king and the rook to be empty, and a rook in the corner can't be pinned,
+/// so the rook can always move to the king's destination square as well.
+pub(crate) fn can_reach(board: &Board, dst_square: SquareCoords) -> bool {
+    let legality = Legality::new(board);
+
+    for (square, piece) in board.pieces_of(board.active_color) {
+        if legality.king_moves_only() && piece != Piece::King(board.active_color) {
+            continue;
+        }
+
+        // skip the legality checks of pieces that can't get there anyway
+        if !piece_destinations(board, square).contains(&dst_square) {
+            continue;
+        }
+
+        if legal_piece_moves(&piece, square, board, &legality)
+            .iter()
+            .any(|r#move| r#move.dst_square == Some(dst_square))
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Returns true if the same move appears more than once in the given list.
+fn has_duplicates(moves: &[Move]) -> bool {
+    moves
+        .iter()
+        .enumerate()
+        .any(|(i, r#move)| moves[..i].contains(r#move))
+}
+
+/// Returns the squares the piece located at the given square could move to
+/// according to its movement rules alone, regardless of whose turn it is and
+/// whether the move would leave its king in check. Castles are not included.
+pub(crate) fn piece_destinations(board: &Board, src_square: SquareCoords) -> Vec<SquareCoords> {
+    let mut destinations = Vec::new();
+
+    let piece = match board.get_piece(src_square) {
+        Some(piece) => piece,
+        None => return destinations,
+    };
+    let color = *piece.color();
+
+    for direction in &piece.directions() {
+        let mut dst_square = src_square + direction;
+
+        while dst_square.inside_board() {
+            let dst_square_piece = board.get_piece(dst_square);
+
+            // pieces of the same color block the way
+            if dst_square_piece.is_some_and(|p| p.color() == &color) {
+                break;
+            }
+
+            if let Piece::Pawn(_) = piece {
+                let valid = match direction {
+                    // forward moves can't capture
+                    (_, 0) if direction.0.abs() == 1 => dst_square_piece.is_none(),
+                    // two square moves only from the starting row and when
+                    // both squares are empty
+                    (_, 0) => {
+                        let start_row = match color {
+                            Color::White => 6,
+                            Color::Black => 1,
+                        };
+                        let middle_square = src_square + (direction.0 / 2, 0);
+
+                        src_square.0 == start_row
+                            && dst_square_piece.is_none()
+                            && board.get_piece(middle_square).is_none()
+                    }
+                    // diagonal moves only when capturing
+                    _ => dst_square_piece.is_some() || board.en_passant_target == Some(dst_square),
+                };
+
+                if valid {
+                    destinations.push(dst_square);
+                }
+
+                break;
+            }
+
+            destinations.push(dst_square);
+
+            // pieces of the opposite color can be captured but not passed
+            if dst_square_piece.is_some() {
+                break;
+            }
+
+            dst_square += direction;
+
+            match piece {
+                Piece::Queen(_) => continue,
+                Piece::Rook(_) => continue,
+                Piece::Bishop(_) => continue,
+                Piece::Knight(_) => break,
+                Piece::King(_) => break,
+                Piece::Pawn(_) => break,
+            }
+        }
+    }
+
+    destinations
+}
+
+/// Returns every square attacked by the pieces of the given color in a single
+/// sweep over the board. Squares occupied by a piece are included when they
+/// are attacked, no matter the color of the piece.
+pub(crate) fn attacked_squares(board: &Board, color: Color) -> [[bool; 8]; 8] {
+    let mut attacked = [[false; 8]; 8];
+
+    for (src_square, piece) in board.pieces_of(color) {
+        for direction in &piece.directions() {
+            // pawns only attack diagonally
+            if let Piece::Pawn(_) = piece {
+                if direction.1 == 0 {
+                    continue;
+                }
+            }
+
+            let mut dst_square = src_square + direction;
+
+            while dst_square.inside_board() {
+                attacked[dst_square.0][dst_square.1] = true;
+
+                // any piece stops the attack beyond it
+                if board.get_piece(dst_square).is_some() {
+                    break;
+                }
+
+                dst_square += direction;
+
+                match piece {
+                    Piece::Queen(_) => continue,
+                    Piece::Rook(_) => continue,
+                    Piece::Bishop(_) => continue,
+                    Piece::Knight(_) => break,
+                    Piece::King(_) => break,
+                    Piece::Pawn(_) => break,
+                }
+            }
+        }
+    }
+
+    attacked
+}
+
 /// Returns a vec of [Move] containing all possible legal moves for the given
 /// piece in the current position.
-fn legal_piece_moves(piece: &Piece, src_square: SquareCoords, board: &Board) -> Vec<Move> {
+fn legal_piece_moves(
+    piece: &Piece,
+    src_square: SquareCoords,
+    board: &Board,
+    legality: &Legality,
+) -> Vec<Move> {
     let mut legal_moves = Vec::new();
 
-    // handle pawn moves separately
-    if let Piece::Pawn(_) = piece {
-        return pawn_legal_moves(src_square, board);
+    // handle pawn and king moves separately
+    match piece {
+        Piece::Pawn(_) => return pawn_legal_moves(src_square, board, legality),
+        Piece::King(_) => return king_legal_moves(src_square, board),
+        _ => (),
     }
 
     for direction in &piece.directions() {
@@ -59,7 +376,7 @@ fn legal_piece_moves(piece: &Piece, src_square: SquareCoords, board: &Board) ->
             // if the piece is the opposite color, we can move there and take it, but not
             // beyond
             if dst_square_piece.is_some_and(|p| p.color() != &board.active_color) {
-                if !board.future_check(&r#move) {
+                if legality.is_legal(board, &r#move) {
                     legal_moves.push(r#move);
                 }
 
@@ -67,7 +384,7 @@ fn legal_piece_moves(piece: &Piece, src_square: SquareCoords, board: &Board) ->
             }
 
             // if the square is empty don't move our king into check or move a pinned piece
-            if !board.future_check(&r#move) {
+            if legality.is_legal(board, &r#move) {
                 legal_moves.push(r#move);
             }
 
@@ -79,7 +396,9 @@ fn legal_piece_moves(piece: &Piece, src_square: SquareCoords, board: &Board) ->
                 Piece::Bishop(_) => continue,
                 Piece::Knight(_) => break,
                 Piece::King(_) => break,
-                Piece::Pawn(_) => unreachable!(),
+                // pawns are handled by pawn_legal_moves, bail out instead of
+                // panicking if one ever ends up here
+                Piece::Pawn(_) => return Vec::new(),
             }
         }
     }
@@ -87,9 +406,45 @@ fn legal_piece_moves(piece: &Piece, src_square: SquareCoords, board: &Board) ->
     legal_moves
 }
 
+/// Returns a vec of [Move] containing all possible legal moves for the king
+/// located at the given square in the current position.
+fn king_legal_moves(src_square: SquareCoords, board: &Board) -> Vec<Move> {
+    let piece = Piece::King(board.active_color);
+
+    // the attacked squares are computed once, with the king removed from the
+    // board so it doesn't shield the squares behind it from sliding pieces
+    let mut board_without_king = board.for_search();
+    board_without_king.set_piece(src_square, None);
+    let attacked = attacked_squares(&board_without_king, board.active_color.invert());
+
+    piece
+        .directions()
+        .iter()
+        .map(|direction| src_square + direction)
+        .filter(|dst_square| dst_square.inside_board() && !attacked[dst_square.0][dst_square.1])
+        .filter_map(|dst_square| {
+            let dst_square_piece = board.get_piece(dst_square);
+
+            if dst_square_piece.is_some_and(|p| p.color() == &board.active_color) {
+                return None;
+            }
+
+            Some(Move {
+                piece: Some(piece),
+                color: board.active_color,
+                src_square: Some(src_square),
+                dst_square: Some(dst_square),
+                promotion: None,
+                castle: None,
+                capture: dst_square_piece.is_some(),
+            })
+        })
+        .collect()
+}
+
 /// Returns a vec of [Move] containing all possible legal moves for the given
 /// pawn in the current position.
-fn pawn_legal_moves(src_square: SquareCoords, board: &Board) -> Vec<Move> {
+fn pawn_legal_moves(src_square: SquareCoords, board: &Board, legality: &Legality) -> Vec<Move> {
     let mut legal_moves = Vec::new();
     let piece = Piece::Pawn(board.active_color);
 
@@ -159,7 +514,7 @@ fn pawn_legal_moves(src_square: SquareCoords, board: &Board) -> Vec<Move> {
                 };
 
                 // don't move the pawn if it is pinned
-                if board.future_check(&r#move) {
+                if !legality.is_legal(board, &r#move) {
                     break;
                 }
 
@@ -180,7 +535,7 @@ fn pawn_legal_moves(src_square: SquareCoords, board: &Board) -> Vec<Move> {
         };
 
         // don't move the pawn if it is pinned
-        if !board.future_check(&r#move) {
+        if legality.is_legal(board, &r#move) {
             legal_moves.push(r#move);
         }
     }
@@ -193,9 +548,13 @@ fn pawn_legal_moves(src_square: SquareCoords, board: &Board) -> Vec<Move> {
 fn legal_castle_moves(board: &Board) -> Vec<Move> {
     let mut legal_moves = Vec::new();
 
+    // only the squares the king stands on, crosses and lands on must not be
+    // attacked. The rook may pass through an attacked square, which is why b1
+    // and b8 only need to be empty.
     match board.active_color {
         Color::White => {
             if board.castle_rights.contains(&CastleRights::WhiteKingside)
+                && board.square_attackers((7, 4).into()).is_empty()
                 && board.get_piece((7, 5).into()).is_none()
                 && board.get_piece((7, 6).into()).is_none()
                 && board.square_attackers((7, 5).into()).is_empty()
@@ -205,6 +564,7 @@ fn legal_castle_moves(board: &Board) -> Vec<Move> {
             }
 
             if board.castle_rights.contains(&CastleRights::WhiteQueenside)
+                && board.square_attackers((7, 4).into()).is_empty()
                 && board.get_piece((7, 1).into()).is_none()
                 && board.get_piece((7, 2).into()).is_none()
                 && board.get_piece((7, 3).into()).is_none()
@@ -217,6 +577,7 @@ fn legal_castle_moves(board: &Board) -> Vec<Move> {
 
         Color::Black => {
             if board.castle_rights.contains(&CastleRights::BlackKingside)
+                && board.square_attackers((0, 4).into()).is_empty()
                 && board.get_piece((0, 5).into()).is_none()
                 && board.get_piece((0, 6).into()).is_none()
                 && board.square_attackers((0, 5).into()).is_empty()
@@ -226,6 +587,7 @@ fn legal_castle_moves(board: &Board) -> Vec<Move> {
             }
 
             if board.castle_rights.contains(&CastleRights::BlackQueenside)
+                && board.square_attackers((0, 4).into()).is_empty()
                 && board.get_piece((0, 1).into()).is_none()
                 && board.get_piece((0, 2).into()).is_none()
                 && board.get_piece((0, 3).into()).is_none()
@@ -288,15 +650,226 @@ mod test {
         assert_eq!(board.legal_moves().len(), 33);
     }
 
+    #[test]
+    fn test_legal_moves_no_duplicates() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "r1k4r/1P6/8/8/8/8/8/R3K2R w KQ - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let legal_moves = generate_legal_moves(&board);
+
+            let mut deduped = Vec::new();
+            for r#move in &legal_moves {
+                if !deduped.contains(r#move) {
+                    deduped.push(*r#move);
+                }
+            }
+
+            assert_eq!(legal_moves.len(), deduped.len(), "{}", fen);
+            assert!(!has_duplicates(&legal_moves));
+        }
+    }
+
+    fn perft(board: &Board, depth: u32, legality: fn(&Board) -> Legality) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        generate_moves(board, &legality(board))
+            .iter()
+            .map(|r#move| {
+                let mut board = board.clone();
+                board.apply_move(r#move);
+                perft(&board, depth - 1, legality)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_legal_moves_fast_path() {
+        for (fen, depth, nodes) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                3,
+                8902,
+            ),
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                2,
+                2039,
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3, 2812),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                2,
+                264,
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                2,
+                1486,
+            ),
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+
+            assert_eq!(perft(&board, depth, Legality::new), nodes, "{}", fen);
+            assert_eq!(
+                perft(&board, depth, |_| Legality::validate_all()),
+                nodes,
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_fast_path_random_games() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for _ in 0..20 {
+            let mut board = Board::new();
+
+            for _ in 0..80 {
+                let fast = generate_moves(&board, &Legality::new(&board));
+                let slow = generate_moves(&board, &Legality::validate_all());
+                assert_eq!(fast, slow, "{}", board.fen());
+
+                if fast.is_empty() {
+                    break;
+                }
+
+                board.apply_move(&fast[rng.gen_range(0..fast.len())]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_can_reach() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4q2k/8/8/b7/8/8/3NR3/rNB1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/3q4/4K3 w - - 0 1",
+        ];
+
+        // agrees with the full list of legal moves on every square
+        for fen in fens {
+            let board = Board::from_fen(fen).unwrap();
+            let legal_moves = generate_legal_moves(&board);
+
+            for index in 0..64 {
+                let square = SquareCoords::from_index(index).unwrap();
+                let reachable = legal_moves.iter().any(|m| m.squares().1 == square);
+
+                assert_eq!(can_reach(&board, square), reachable, "{} {}", fen, square);
+            }
+        }
+
+        // including every position of a few random games, with castles
+        let mut rng = StdRng::seed_from_u64(93);
+
+        for _ in 0..5 {
+            let (_, moves, _) = Board::play_random_game(&mut rng);
+            let mut board = Board::new();
+
+            for r#move in &moves {
+                let legal_moves = generate_legal_moves(&board);
+
+                for index in 0..64 {
+                    let square = SquareCoords::from_index(index).unwrap();
+                    let reachable = legal_moves.iter().any(|m| m.squares().1 == square);
+
+                    assert_eq!(can_reach(&board, square), reachable, "{}", board.fen());
+                }
+
+                board.apply_unchecked(r#move);
+            }
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_in_check() {
+        let legal_moves = |fen: &str| {
+            let board = Board::from_fen(fen).unwrap();
+            let fast = generate_moves(&board, &Legality::new(&board));
+            let slow = generate_moves(&board, &Legality::validate_all());
+            assert_eq!(fast, slow, "{}", fen);
+
+            let mut ucis = fast.iter().map(Move::to_uci_str).collect::<Vec<_>>();
+            ucis.sort();
+            ucis
+        };
+
+        // single check by a rook: capture it, block on f1..b1 or move the king
+        let fen = "4k3/8/8/8/8/2N5/5B2/r3K3 w - - 0 1";
+        let legality = Legality::new(&Board::from_fen(fen).unwrap());
+        assert_eq!(legality.checkers, 1);
+        assert!(!legality.king_moves_only());
+        assert_eq!(
+            legality.check_mask.iter().flatten().filter(|&&s| s).count(),
+            4
+        );
+        assert_eq!(legal_moves(fen), ["c3b1", "c3d1", "e1d2", "e1e2"]);
+
+        // single check by a pawn that can be taken en passant
+        let fen = "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1";
+        assert!(legal_moves(fen).contains(&"e4d3".to_string()));
+
+        // double check by a knight and a rook: only the king moves
+        let fen = "4k3/8/8/8/8/3n4/1B3P2/4K2r w - - 0 1";
+        let legality = Legality::new(&Board::from_fen(fen).unwrap());
+        assert_eq!(legality.checkers, 2);
+        assert!(legality.king_moves_only());
+        assert_eq!(legal_moves(fen), ["e1d2", "e1e2"]);
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        // the knight on d2 is pinned by the bishop and the rook on e2 by the
+        // queen, while the bishop on c1 is not since the knight on b1 also
+        // stands between the king and the rook
+        let board = Board::from_fen("4q2k/8/8/b7/8/8/3NR3/rNB1K3 w - - 0 1").unwrap();
+        let legality = Legality::new(&board);
+
+        let pinned = |san: &str| {
+            let square = SquareCoords::from_san_str(san).unwrap();
+            legality.pinned[square.0][square.1]
+        };
+
+        assert!(pinned("d2"));
+        assert!(pinned("e2"));
+        assert_eq!(legality.checkers, 0);
+        assert_eq!(
+            legality
+                .pinned
+                .iter()
+                .flatten()
+                .filter(|&&pinned| pinned)
+                .count(),
+            2
+        );
+    }
+
     #[test]
     fn test_pawn_legal_moves() {
         // frontal pinned pawn
         let mut board =
             Board::from_fen("rnb1kbnr/ppp1pppp/4q3/3p4/P3P3/8/1PPP1PPP/RNBQKBNR w KQkq - 1 4")
                 .unwrap();
-        assert_eq!(pawn_legal_moves((4, 4).into(), &board).len(), 1);
         assert_eq!(
-            pawn_legal_moves((4, 4).into(), &board)[0],
+            pawn_legal_moves((4, 4).into(), &board, &Legality::new(&board)).len(),
+            1
+        );
+        assert_eq!(
+            pawn_legal_moves((4, 4).into(), &board, &Legality::new(&board))[0],
             Move {
                 piece: Some(Piece::Pawn(Color::White)),
                 color: Color::White,
@@ -311,24 +884,36 @@ mod test {
         // diagonal pinned pawn
         board = Board::from_fen("rnb1kbnr/ppp1pppp/8/q2p4/4P3/8/1PPP1PPP/RNBQKBNR w KQkq - 0 5")
             .unwrap();
-        assert_eq!(pawn_legal_moves((6, 3).into(), &board).len(), 0);
+        assert_eq!(
+            pawn_legal_moves((6, 3).into(), &board, &Legality::new(&board)).len(),
+            0
+        );
 
         // en passant
         board = Board::from_fen("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")
             .unwrap();
-        assert_eq!(pawn_legal_moves((3, 4).into(), &board).len(), 2);
+        assert_eq!(
+            pawn_legal_moves((3, 4).into(), &board, &Legality::new(&board)).len(),
+            2
+        );
 
         // blocking pawn (one square move)
         board =
             Board::from_fen("rnbqkbnr/1ppppppp/8/p7/P7/8/1PPPPPPP/RNBQKBNR w KQkq - 0 2").unwrap();
-        assert_eq!(pawn_legal_moves((4, 0).into(), &board).len(), 0);
+        assert_eq!(
+            pawn_legal_moves((4, 0).into(), &board, &Legality::new(&board)).len(),
+            0
+        );
 
         // blocking pawn (two square move)
         board =
             Board::from_fen("rnbqkbnr/1ppppppp/p7/8/P7/8/1PPPPPPP/RNBQKBNR w KQkq - 0 2").unwrap();
-        assert_eq!(pawn_legal_moves((4, 0).into(), &board).len(), 1);
         assert_eq!(
-            pawn_legal_moves((4, 0).into(), &board)[0],
+            pawn_legal_moves((4, 0).into(), &board, &Legality::new(&board)).len(),
+            1
+        );
+        assert_eq!(
+            pawn_legal_moves((4, 0).into(), &board, &Legality::new(&board))[0],
             Move {
                 piece: Some(Piece::Pawn(Color::White)),
                 color: Color::White,
@@ -343,17 +928,26 @@ mod test {
         // capture
         board = Board::from_fen("rn2kbnr/pppqp1pp/8/3p1p2/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 5")
             .unwrap();
-        assert_eq!(pawn_legal_moves((4, 4).into(), &board).len(), 3);
+        assert_eq!(
+            pawn_legal_moves((4, 4).into(), &board, &Legality::new(&board)).len(),
+            3
+        );
 
         // promotion
         board =
             Board::from_fen("r2qkbnr/pPppppp1/b1n4p/8/8/8/PP1PPPPP/RNBQKBNR w KQkq - 0 5").unwrap();
-        assert_eq!(pawn_legal_moves((1, 1).into(), &board).len(), 8);
+        assert_eq!(
+            pawn_legal_moves((1, 1).into(), &board, &Legality::new(&board)).len(),
+            8
+        );
 
         // promotion pinned
         board =
             Board::from_fen("r2qkbnr/pPppppp1/b1n4p/8/8/8/PP1PPPPP/RNBQKBNR w KQkq - 0 5").unwrap();
-        assert_eq!(pawn_legal_moves((1, 3).into(), &board).len(), 4);
+        assert_eq!(
+            pawn_legal_moves((1, 3).into(), &board, &Legality::new(&board)).len(),
+            4
+        );
     }
 
     #[test]
@@ -361,14 +955,26 @@ mod test {
         // king can't move
         let mut board = Board::from_fen("R7/2p5/8/2k3p1/1r6/K1P5/PP6/8 w - - 6 43").unwrap();
         assert_eq!(
-            legal_piece_moves(&Piece::King(Color::White), (5, 0).into(), &board).len(),
+            legal_piece_moves(
+                &Piece::King(Color::White),
+                (5, 0).into(),
+                &board,
+                &Legality::new(&board)
+            )
+            .len(),
             0
         );
 
         // king under check
         board = Board::from_fen("5R2/2p5/8/2k3p1/r7/K1P5/PP6/8 w - - 8 44").unwrap();
         assert_eq!(
-            legal_piece_moves(&Piece::King(Color::White), (5, 0).into(), &board).len(),
+            legal_piece_moves(
+                &Piece::King(Color::White),
+                (5, 0).into(),
+                &board,
+                &Legality::new(&board)
+            )
+            .len(),
             2
         );
 
@@ -376,11 +982,141 @@ mod test {
         board = Board::from_fen("rnbqk1nr/1pppbppp/p7/8/4QB2/P7/1PP1PPPP/RN2KBNR b KQkq - 3 5")
             .unwrap();
         assert_eq!(
-            legal_piece_moves(&Piece::Bishop(Color::Black), (1, 4).into(), &board).len(),
+            legal_piece_moves(
+                &Piece::Bishop(Color::Black),
+                (1, 4).into(),
+                &board,
+                &Legality::new(&board)
+            )
+            .len(),
             0
         );
     }
 
+    #[test]
+    fn test_promotion_captures() {
+        let board = Board::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotion_captures = board
+            .legal_moves()
+            .into_iter()
+            .filter(|m| m.dst_square == Some((0, 0).into()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(promotion_captures.len(), 4);
+        assert!(promotion_captures.iter().all(|m| m.capture));
+        for piece in [
+            Piece::Queen(Color::White),
+            Piece::Rook(Color::White),
+            Piece::Bishop(Color::White),
+            Piece::Knight(Color::White),
+        ] {
+            assert!(promotion_captures
+                .iter()
+                .any(|m| m.promotion == Some(piece)));
+        }
+
+        // the push to b8 promotes as well
+        assert_eq!(
+            pawn_legal_moves((1, 1).into(), &board, &Legality::new(&board)).len(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_attacked_squares() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnb2k1r/pp1Pbppp/2p5/q7/2B5/8/PPPQNnPP/RNB1K2R w KQ - 3 9",
+            "3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+
+            for color in [Color::White, Color::Black] {
+                let attacked = attacked_squares(&board, color);
+
+                for (row, attacked_row) in attacked.iter().enumerate() {
+                    for (col, &attacked_square) in attacked_row.iter().enumerate() {
+                        let square = SquareCoords(row, col);
+                        assert_eq!(
+                            attacked_square,
+                            !board.square_attackers_by(square, color).is_empty(),
+                            "{} attacked by {} in {}",
+                            square,
+                            color,
+                            fen
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_king_legal_moves() {
+        // the king can't step back along the ray of the checking rook
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+        let king_moves = king_legal_moves((7, 4).into(), &board);
+        assert_eq!(king_moves.len(), 3);
+        assert!(king_moves.iter().all(|m| m.dst_square.unwrap().0 == 6));
+
+        // defended pieces can't be captured, undefended ones can
+        let board = Board::from_fen("4k3/8/8/8/8/8/3qp3/4K3 w - - 0 1").unwrap();
+        let king_moves = king_legal_moves((7, 4).into(), &board);
+        assert_eq!(king_moves.len(), 2);
+        assert!(king_moves
+            .iter()
+            .any(|m| m.dst_square == Some((6, 3).into()) && m.capture));
+        assert!(king_moves
+            .iter()
+            .any(|m| m.dst_square == Some((6, 5).into()) && !m.capture));
+    }
+
+    #[test]
+    fn test_castle_path_after_moves() {
+        let castles = |board: &Board| {
+            legal_castle_moves(board)
+                .iter()
+                .map(|m| m.to_san_str())
+                .collect::<Vec<_>>()
+        };
+
+        // castles available to the side to move after each move
+        let mut board = Board::new();
+        for (r#move, expected) in [
+            ("Nf3", vec![]),
+            ("Nc6", vec![]),
+            ("e4", vec![]),
+            ("d5", vec![]),
+            ("Be2", vec![]),
+            ("Qd6", vec!["O-O"]),
+            // the knight going back blocks the path again
+            ("Ng1", vec![]),
+            ("Bd7", vec![]),
+            ("Nf3", vec!["O-O-O"]),
+            // b8 is not crossed by the king but the rook still needs it empty
+            ("Nb8", vec!["O-O"]),
+            ("d3", vec![]),
+            ("Na6", vec!["O-O"]),
+        ] {
+            assert!(board.make_move(r#move).is_some(), "{}", r#move);
+            assert_eq!(castles(&board), expected, "after {}", r#move);
+            assert_eq!(
+                castles(&board),
+                castles(&Board::from_fen(&board.fen()).unwrap()),
+                "after {}",
+                r#move
+            );
+        }
+
+        board.make_move("O-O");
+        assert_eq!(
+            board.fen(),
+            "r3kbnr/pppbpppp/n2q4/3p4/4P3/3P1N2/PPP1BPPP/RNBQ1RK1 b kq - 2 7"
+        );
+    }
+
     #[test]
     fn test_castle_legal_moves() {
         // white kingside and queenside
@@ -425,5 +1161,20 @@ mod test {
             legal_castle_moves(&board)[0].castle,
             Some(CastleKind::Queenside)
         );
+
+        // only the rook's path is attacked, the king's path is safe
+        board = Board::from_fen("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        assert_eq!(legal_castle_moves(&board).len(), 1);
+        assert_eq!(
+            legal_castle_moves(&board)[0].castle,
+            Some(CastleKind::Queenside)
+        );
+
+        board = Board::from_fen("4k3/8/8/8/8/8/8/Rr2K3 w Q - 0 1").unwrap();
+        assert_eq!(legal_castle_moves(&board).len(), 0);
+
+        // the king can't castle out of check
+        board = Board::from_fen("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(legal_castle_moves(&board).len(), 0);
     }
 }
@@ -0,0 +1,47 @@
+use crate::core::Color;
+
+/// Represents the reason why the active player has no legal moves.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NoMovesReason {
+    Checkmate,
+    Stalemate,
+}
+
+/// Represents the way a game has ended or can be ended.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The given color has checkmated its opponent.
+    Checkmate(Color),
+    Stalemate,
+    InsufficientMaterial,
+    SeventyFiveMoveRule,
+    FivefoldRepetition,
+    /// Draw that has to be claimed by one of the players.
+    ThreefoldRepetition,
+    /// Draw that has to be claimed by one of the players.
+    FiftyMoveRule,
+}
+
+impl Outcome {
+    /// Returns the winner of the game, or `None` if the game is drawn.
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            Outcome::Checkmate(color) => Some(*color),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outcome::Checkmate(color) => write!(f, "Checkmate, {} wins", color),
+            Outcome::Stalemate => write!(f, "Draw by stalemate"),
+            Outcome::InsufficientMaterial => write!(f, "Draw by insufficient material"),
+            Outcome::SeventyFiveMoveRule => write!(f, "Draw by the seventy-five-move rule"),
+            Outcome::FivefoldRepetition => write!(f, "Draw by fivefold repetition"),
+            Outcome::ThreefoldRepetition => write!(f, "Draw by threefold repetition"),
+            Outcome::FiftyMoveRule => write!(f, "Draw by the fifty-move rule"),
+        }
+    }
+}
@@ -5,7 +5,7 @@ use crate::constants::{
 use crate::core::Color;
 
 /// Represents a chess piece.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Piece {
     Pawn(Color),
     Knight(Color),
@@ -15,6 +15,17 @@ pub enum Piece {
     King(Color),
 }
 
+/// Represents the kind of a chess piece, regardless of its color.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
 impl Piece {
     /// Tries to create a piece from a FEN character.
     pub fn from_fen_char(c: char) -> Option<Piece> {
@@ -120,6 +131,31 @@ impl Piece {
         }
     }
 
+    /// Returns the conventional material value of the piece in pawn units.
+    /// The king can't be captured, so it has no material value.
+    pub fn value(&self) -> i32 {
+        match self {
+            Piece::Pawn(_) => 1,
+            Piece::Knight(_) => 3,
+            Piece::Bishop(_) => 3,
+            Piece::Rook(_) => 5,
+            Piece::Queen(_) => 9,
+            Piece::King(_) => 0,
+        }
+    }
+
+    /// Returns the kind of the piece.
+    pub fn kind(&self) -> PieceKind {
+        match self {
+            Piece::Pawn(_) => PieceKind::Pawn,
+            Piece::Knight(_) => PieceKind::Knight,
+            Piece::Bishop(_) => PieceKind::Bishop,
+            Piece::Rook(_) => PieceKind::Rook,
+            Piece::Queen(_) => PieceKind::Queen,
+            Piece::King(_) => PieceKind::King,
+        }
+    }
+
     /// Returns the color of the piece.
     pub fn color(&self) -> &Color {
         match self {
@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 /// Represents a square on the board.
 /// The first element represents the row and the second element the column.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct SquareCoords(pub usize, pub usize);
 
 impl SquareCoords {
@@ -27,6 +27,30 @@ impl SquareCoords {
     pub fn inside_board(&self) -> bool {
         (0..=7).contains(&self.0) && (0..=7).contains(&self.1)
     }
+
+    /// Tries to create a square from its index, going from 0 for a1 to 63 for
+    /// h8 rank by rank, the layout commonly used by bitboards.
+    pub fn from_index(index: usize) -> Option<SquareCoords> {
+        match index {
+            0..=63 => Some(SquareCoords(7 - index / 8, index % 8)),
+            _ => None,
+        }
+    }
+
+    /// Returns the index of the square, going from 0 for a1 to 63 for h8 rank
+    /// by rank. The result is only meaningful for squares inside the board.
+    pub fn index(&self) -> usize {
+        (7 - self.0) * 8 + self.1
+    }
+
+    /// Returns the algebraic notation of the square (e.g. "e4"), or `None` if
+    /// the square coordinates don't form part of the board.
+    pub fn to_algebraic(&self) -> Option<String> {
+        match self.inside_board() {
+            true => Some(self.to_string()),
+            false => None,
+        }
+    }
 }
 
 impl Display for SquareCoords {
@@ -46,6 +70,21 @@ impl From<(usize, usize)> for SquareCoords {
     }
 }
 
+impl From<SquareCoords> for u8 {
+    fn from(square: SquareCoords) -> Self {
+        square.index() as u8
+    }
+}
+
+impl TryFrom<u8> for SquareCoords {
+    type Error = u8;
+
+    /// Fails with the given index if it is not between 0 and 63.
+    fn try_from(index: u8) -> Result<Self, Self::Error> {
+        SquareCoords::from_index(index as usize).ok_or(index)
+    }
+}
+
 impl PartialEq<(usize, usize)> for SquareCoords {
     fn eq(&self, (row, col): &(usize, usize)) -> bool {
         self.0 == *row && self.1 == *col
@@ -95,3 +134,51 @@ impl std::ops::AddAssign<(i8, i8)> for SquareCoords {
         *self = *self + (row, col);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_algebraic() {
+        assert_eq!(SquareCoords(4, 4).to_algebraic(), Some("e4".to_string()));
+        assert_eq!(SquareCoords(0, 0).to_algebraic(), Some("a8".to_string()));
+        assert_eq!(SquareCoords(7, 7).to_algebraic(), Some("h1".to_string()));
+
+        // off-board coordinates, including the ones wrapped by arithmetic
+        assert_eq!(SquareCoords(8, 0).to_algebraic(), None);
+        assert_eq!(SquareCoords(0, 8).to_algebraic(), None);
+        assert_eq!((SquareCoords(0, 0) + (-1, 0)).to_algebraic(), None);
+        assert_eq!((SquareCoords(7, 7) + (0, 1)).to_algebraic(), None);
+    }
+
+    #[test]
+    fn test_square_index() {
+        for index in 0..64u8 {
+            let square = SquareCoords::try_from(index).unwrap();
+            assert!(square.inside_board());
+            assert_eq!(u8::from(square), index);
+            assert_eq!(SquareCoords::from_index(index as usize), Some(square));
+        }
+
+        assert_eq!(
+            SquareCoords::from_index(0),
+            SquareCoords::from_san_str("a1")
+        );
+        assert_eq!(
+            SquareCoords::from_index(7),
+            SquareCoords::from_san_str("h1")
+        );
+        assert_eq!(
+            SquareCoords::from_index(28),
+            SquareCoords::from_san_str("e4")
+        );
+        assert_eq!(
+            SquareCoords::from_index(63),
+            SquareCoords::from_san_str("h8")
+        );
+
+        assert_eq!(SquareCoords::from_index(64), None);
+        assert_eq!(SquareCoords::try_from(64u8), Err(64));
+    }
+}
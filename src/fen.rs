@@ -1,4 +1,6 @@
-use crate::core::{Board, CastleRights, Color, Piece, SquareCoords};
+use std::fmt::Write;
+
+use crate::core::{Board, BoardError, CastleRights, Color, Piece, SquareCoords};
 
 /// Represents errors that can occur when parsing a FEN string.
 #[derive(Debug)]
@@ -10,6 +12,8 @@ pub enum FenParseError {
     EnPassant,
     HalfmoveClock,
     FullmoveNumber,
+    TooManyKings(Color),
+    Material(BoardError),
 }
 
 impl std::error::Error for FenParseError {}
@@ -24,6 +28,8 @@ impl std::fmt::Display for FenParseError {
             FenParseError::EnPassant => write!(f, "Invalid en passant"),
             FenParseError::HalfmoveClock => write!(f, "Invalid halfmove clock"),
             FenParseError::FullmoveNumber => write!(f, "Invalid fullmove number"),
+            FenParseError::TooManyKings(color) => write!(f, "More than one {} king", color),
+            FenParseError::Material(error) => write!(f, "Invalid material: {}", error),
         }
     }
 }
@@ -32,6 +38,48 @@ impl std::fmt::Display for FenParseError {
 /// [Forsyth–Edwards Notation](https://www.chess.com/terms/fen-chess) (FEN) is a standard notation for describing a particular board position of a chess game.
 /// TODO: make full validation of the FEN string
 pub fn fen_to_board(fen_string: &str) -> Result<Board, FenParseError> {
+    parse_fen(fen_string, false)
+}
+
+/// Same as [fen_to_board], but tolerates some common mistakes of FEN
+/// generators instead of returning an error: a fullmove number of 0 is read
+/// as 1, and the material is not checked to be reachable in a game (see
+/// [Board::validate_material()](crate::Board::validate_material())).
+pub fn fen_to_board_lenient(fen_string: &str) -> Result<Board, FenParseError> {
+    parse_fen(fen_string, true)
+}
+
+/// Sets the given board to the position of the FEN string, reusing the
+/// allocations of its castle rights and position history. The board is left
+/// untouched if the FEN string is not valid.
+pub fn fen_to_board_in_place(board: &mut Board, fen_string: &str) -> Result<(), FenParseError> {
+    parse_fen_into(board, fen_string, false)
+}
+
+/// Creates a new board from the given FEN string, either strictly or
+/// leniently, see [fen_to_board] and [fen_to_board_lenient].
+fn parse_fen(fen_string: &str, lenient: bool) -> Result<Board, FenParseError> {
+    let mut board = Board {
+        squares: [[None; 8]; 8],
+        active_color: Color::White,
+        castle_rights: Vec::new(),
+        en_passant_target: None,
+        halfmove_clock: 0,
+        fullmove_number: 1,
+        position_history: Vec::new(),
+        white_king_moved: false,
+        black_king_moved: false,
+        legal_moves_cache: Default::default(),
+        undo_stack: None,
+    };
+
+    parse_fen_into(&mut board, fen_string, lenient)?;
+
+    Ok(board)
+}
+
+/// Sets the given board to the position of the FEN string, see [parse_fen].
+fn parse_fen_into(board: &mut Board, fen_string: &str, lenient: bool) -> Result<(), FenParseError> {
     let mut squares = [[None; 8]; 8];
     let fen_blocks: Vec<&str> = fen_string.split_whitespace().collect();
 
@@ -49,6 +97,7 @@ pub fn fen_to_board(fen_string: &str) -> Result<Board, FenParseError> {
     for (i, row) in piece_placement.enumerate() {
         let mut col = 0;
         let mut row_count = 0;
+        let mut previous_digit = false;
 
         for c in row.chars() {
             if row_count > 7 {
@@ -56,11 +105,19 @@ pub fn fen_to_board(fen_string: &str) -> Result<Board, FenParseError> {
             }
 
             if c.is_ascii_digit() {
+                // consecutive empty squares must be written as a single digit,
+                // so a rank like "44" is not valid
+                if previous_digit {
+                    return Err(FenParseError::PiecePositions);
+                }
+
                 let digit = c.to_digit(10).ok_or(FenParseError::PiecePositions)? as usize;
                 col += digit;
                 row_count += digit;
             }
 
+            previous_digit = c.is_ascii_digit();
+
             if c.is_ascii_alphabetic() {
                 let piece = Piece::from_fen_char(c).ok_or(FenParseError::PiecePositions)?;
                 squares[i][col] = Some(piece);
@@ -74,18 +131,32 @@ pub fn fen_to_board(fen_string: &str) -> Result<Board, FenParseError> {
         }
     }
 
+    // a side can't have more than one king
+    for color in [Color::White, Color::Black] {
+        let kings = squares
+            .iter()
+            .flatten()
+            .filter(|&&piece| piece == Some(Piece::King(color)))
+            .count();
+
+        if kings > 1 {
+            return Err(FenParseError::TooManyKings(color));
+        }
+    }
+
+    // some generators write the active color in uppercase
     let active_color = match *fen_blocks.get(1).ok_or(FenParseError::FenString)? {
-        "w" => Color::White,
-        "b" => Color::Black,
+        "w" | "W" => Color::White,
+        "b" | "B" => Color::Black,
         _ => return Err(FenParseError::ActiveColor),
     };
 
-    let mut castle_rights = Vec::new();
-    for c in fen_blocks.get(2).ok_or(FenParseError::FenString)?.chars() {
-        match c {
-            '-' => continue,
-            _ => castle_rights
-                .push(CastleRights::from_fen_char(c).ok_or(FenParseError::CastleRights)?),
+    // only check the castle rights here, they are pushed into the board's
+    // vector once the whole string is known to be valid
+    let castle_rights = fen_blocks.get(2).ok_or(FenParseError::FenString)?;
+    for c in castle_rights.chars() {
+        if c != '-' && CastleRights::from_fen_char(c).is_none() {
+            return Err(FenParseError::CastleRights);
         }
     }
 
@@ -107,44 +178,131 @@ pub fn fen_to_board(fen_string: &str) -> Result<Board, FenParseError> {
         None => 1,
     };
 
-    Ok(Board {
-        squares,
-        active_color,
-        castle_rights,
-        en_passant_target: en_passant,
-        halfmove_clock,
-        fullmove_number,
-        position_history: vec![fen_string.into()],
-    })
+    // the fullmove number starts at 1
+    let fullmove_number = match fullmove_number {
+        0 if lenient => 1,
+        0 => return Err(FenParseError::FullmoveNumber),
+        n => n,
+    };
+
+    // there is no way to know if a king has moved from a FEN string, so assume
+    // it has if it is not on its starting square
+    let white_king_moved = squares[7][4] != Some(Piece::King(Color::White));
+    let black_king_moved = squares[0][4] != Some(Piece::King(Color::Black));
+
+    let previous_squares = std::mem::replace(&mut board.squares, squares);
+
+    if !lenient {
+        if let Err(error) = board.validate_material() {
+            board.squares = previous_squares;
+            return Err(FenParseError::Material(error));
+        }
+    }
+
+    board.active_color = active_color;
+    board.en_passant_target = en_passant;
+    board.halfmove_clock = halfmove_clock;
+    board.fullmove_number = fullmove_number;
+    board.white_king_moved = white_king_moved;
+    board.black_king_moved = black_king_moved;
+    board.legal_moves_cache.clear();
+    board.clear_undo_stack();
+
+    board.castle_rights.clear();
+    board.castle_rights.extend(
+        castle_rights
+            .chars()
+            .filter_map(CastleRights::from_fen_char),
+    );
+
+    // store the normalized FEN so it can be compared with the positions
+    // reached later in the game, reusing the buffer of an old entry if any
+    let mut fen = board.position_history.pop().unwrap_or_default();
+    board.position_history.clear();
+    fen.clear();
+    write_fen(board, &mut fen);
+    board.position_history.push(fen);
+
+    Ok(())
+}
+
+/// Creates a new board from the piece placement field of a FEN string alone.
+/// White is set as the active color, castle rights are given for every king
+/// and rook still on their starting squares, there is no en passant target
+/// and the move counters are set to 0 and 1.
+pub fn placement_to_board(placement: &str) -> Result<Board, FenParseError> {
+    if placement.split_whitespace().count() != 1 {
+        return Err(FenParseError::FenString);
+    }
+
+    let mut board = fen_to_board(&format!("{} w - - 0 1", placement.trim()))?;
+
+    for (castle_right, king_square, rook_square) in [
+        (CastleRights::WhiteKingside, (7, 4), (7, 7)),
+        (CastleRights::WhiteQueenside, (7, 4), (7, 0)),
+        (CastleRights::BlackKingside, (0, 4), (0, 7)),
+        (CastleRights::BlackQueenside, (0, 4), (0, 0)),
+    ] {
+        let color = castle_right.color();
+
+        if board.get_piece(king_square.into()) == Some(Piece::King(color))
+            && board.get_piece(rook_square.into()) == Some(Piece::Rook(color))
+        {
+            board.castle_rights.push(castle_right);
+        }
+    }
+
+    board.position_history = vec![board_to_fen(&board)];
+
+    Ok(board)
+}
+
+/// Parses the given FEN string and writes it back in canonical form, so
+/// equal positions written differently give the same string: castle rights
+/// in KQkq order, the en passant target replaced by `-` when no pawn can
+/// capture on it, and the move counters kept as they are.
+///
+/// # Examples
+///
+/// ```
+/// use chessr::fen::canonicalize;
+///
+/// assert_eq!(
+///     canonicalize("r3k2r/8/8/8/8/8/8/R3K2R W qkQK - 0 1").unwrap(),
+///     "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"
+/// );
+/// ```
+pub fn canonicalize(fen_string: &str) -> Result<String, FenParseError> {
+    let mut board = fen_to_board(fen_string)?;
+
+    board.castle_rights = [
+        CastleRights::WhiteKingside,
+        CastleRights::WhiteQueenside,
+        CastleRights::BlackKingside,
+        CastleRights::BlackQueenside,
+    ]
+    .into_iter()
+    .filter(|right| board.castle_rights.contains(right))
+    .collect();
+    board.en_passant_target = board.capturable_en_passant_target();
+
+    Ok(board_to_fen(&board))
 }
 
 /// Converts a given board to a FEN string.
 /// [Forsyth–Edwards Notation](https://www.chess.com/terms/fen-chess) (FEN) is a standard notation for describing a particular board position of a chess game.
 pub fn board_to_fen(board: &Board) -> String {
     let mut fen = String::new();
+    write_fen(board, &mut fen);
+    fen
+}
 
+/// Appends the FEN string of the given board to `fen` without allocating a
+/// new string.
+pub(crate) fn write_fen(board: &Board, fen: &mut String) {
     // piece placement
     for row in &board.squares {
-        let mut empty_squares = 0;
-
-        for &piece in row {
-            match piece {
-                Some(p) => {
-                    if empty_squares > 0 {
-                        fen.push_str(&empty_squares.to_string());
-                        empty_squares = 0;
-                    }
-
-                    fen.push_str(&p.to_fen_char().to_string());
-                }
-                None => empty_squares += 1,
-            }
-        }
-
-        if empty_squares > 0 {
-            fen.push_str(&empty_squares.to_string());
-        }
-
+        write_row_fen(row, fen);
         fen.push('/');
     }
 
@@ -152,7 +310,7 @@ pub fn board_to_fen(board: &Board) -> String {
     fen.push(' ');
 
     // active color
-    fen.push_str(&board.active_color.to_fen_char().to_string());
+    fen.push(board.active_color.to_fen_char());
     fen.push(' ');
 
     // castle rights
@@ -160,28 +318,152 @@ pub fn board_to_fen(board: &Board) -> String {
         fen.push('-');
     } else {
         for right in &board.castle_rights {
-            fen.push_str(&right.to_fen_char().to_string());
+            fen.push(right.to_fen_char());
         }
     }
 
     fen.push(' ');
 
     // en passant
+    // writing into a String never fails, so the results can be ignored
     match board.en_passant_target {
         Some(square) => {
-            fen.push_str(&square.to_string());
+            let _ = write!(fen, "{}", square);
         }
         None => fen.push('-'),
     }
 
-    fen.push(' ');
+    // halfmove clock and fullmove number
+    let _ = write!(fen, " {} {}", board.halfmove_clock, board.fullmove_number);
+}
 
-    // halfmove clock
-    fen.push_str(&board.halfmove_clock.to_string());
-    fen.push(' ');
+/// Appends the piece placement of a single row of the board to `fen`.
+pub(crate) fn write_row_fen(row: &[Option<Piece>; 8], fen: &mut String) {
+    let mut empty_squares = 0;
 
-    // fullmove number
-    fen.push_str(&board.fullmove_number.to_string());
+    for &piece in row {
+        match piece {
+            Some(p) => {
+                if empty_squares > 0 {
+                    push_digit(fen, empty_squares);
+                    empty_squares = 0;
+                }
 
-    fen
+                fen.push(p.to_fen_char());
+            }
+            None => empty_squares += 1,
+        }
+    }
+
+    if empty_squares > 0 {
+        push_digit(fen, empty_squares);
+    }
+}
+
+/// Pushes a single digit number of empty squares into the FEN string.
+fn push_digit(fen: &mut String, digit: u32) {
+    fen.push(char::from_digit(digit, 10).unwrap_or('0'));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fen_uppercase_active_color() {
+        let board =
+            fen_to_board("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR W KQkq - 0 1").unwrap();
+        assert_eq!(board.active_color, Color::White);
+        assert_eq!(
+            board_to_fen(&board),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(board.position_history, vec![board_to_fen(&board)]);
+
+        let board =
+            fen_to_board("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR B KQkq e3 0 1").unwrap();
+        assert_eq!(board.active_color, Color::Black);
+
+        assert!(matches!(
+            fen_to_board("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1"),
+            Err(FenParseError::ActiveColor)
+        ));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        for (a, b) in [
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w qkQK - 0 1",
+                "r3k2r/8/8/8/8/8/8/R3K2R W KkqQ - 0 1",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR B KQkq - 0 1",
+            ),
+        ] {
+            assert_eq!(canonicalize(a).unwrap(), canonicalize(b).unwrap());
+        }
+
+        assert_eq!(
+            canonicalize("r3k2r/8/8/8/8/8/8/R3K2R w qkQK - 0 1").unwrap(),
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"
+        );
+        assert_eq!(
+            canonicalize("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        // a target a pawn can capture on is kept, and so are the counters
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        assert_eq!(canonicalize(fen).unwrap(), fen);
+        assert_eq!(
+            canonicalize("4k3/8/8/8/8/8/8/4K3 b - - 12 40").unwrap(),
+            "4k3/8/8/8/8/8/8/4K3 b - - 12 40"
+        );
+
+        assert!(canonicalize("4k3/8/8/8/8/8/8/4K3 w X - 0 1").is_err());
+    }
+
+    #[test]
+    fn test_fen_consecutive_digits() {
+        let fen = |rank: &str| fen_to_board(&format!("4k3/{}/8/8/8/8/8/4K3 w - - 0 1", rank));
+
+        assert!(matches!(fen("44"), Err(FenParseError::PiecePositions)));
+        assert!(matches!(fen("p43"), Err(FenParseError::PiecePositions)));
+        assert!(fen("8").is_ok());
+
+        let board = fen("pp4pp").unwrap();
+        assert_eq!(board.rank_fen(7), "pp4pp");
+    }
+
+    #[test]
+    fn test_fen_too_many_kings() {
+        assert!(matches!(
+            fen_to_board("4k3/8/8/8/8/8/8/k3K3 w - - 0 1"),
+            Err(FenParseError::TooManyKings(Color::Black))
+        ));
+        assert!(matches!(
+            fen_to_board("4k3/8/8/8/8/8/8/K3K3 b - - 0 1"),
+            Err(FenParseError::TooManyKings(Color::White))
+        ));
+        assert!(fen_to_board("4k3/8/8/8/8/8/8/4K3 w - - 0 1").is_ok());
+    }
+
+    #[test]
+    fn test_fen_zero_fullmove_number() {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0";
+
+        let board = fen_to_board_lenient(fen).unwrap();
+        assert_eq!(board.fullmove_number, 1);
+        assert_eq!(
+            board_to_fen(&board),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+
+        assert!(matches!(
+            fen_to_board(fen),
+            Err(FenParseError::FullmoveNumber)
+        ));
+    }
 }
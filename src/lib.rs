@@ -2,9 +2,19 @@ pub mod constants;
 pub mod core;
 pub mod fen;
 
+pub use core::BitBoard;
 pub use core::Board;
+pub use core::BoardError;
+pub use core::CheckEscapes;
 pub use core::Color;
 pub use core::Move;
+pub use core::NoMovesReason;
+pub use core::Outcome;
 pub use core::Piece;
+pub use core::PieceKind;
+pub use core::PlayError;
+pub use core::RenderStyle;
+pub use core::SanLanguage;
+pub use core::SanOptions;
 pub use core::SquareCoords;
 pub use core::{CastleKind, CastleRights};
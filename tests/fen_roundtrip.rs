@@ -0,0 +1,29 @@
+use chessr::Board;
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Checks that every position of a few random games survives a round trip
+/// through its FEN string: parsing the FEN gives back the same position and
+/// counters, and writing it again gives back the same string.
+#[test]
+fn test_fen_round_trip_random_games() {
+    let mut rng = StdRng::seed_from_u64(2024);
+
+    for _ in 0..10 {
+        let (_, moves, _) = Board::play_random_game(&mut rng);
+        let mut board = Board::new();
+
+        for r#move in &moves {
+            board.apply_unchecked(r#move);
+
+            let fen = board.fen();
+            let parsed = Board::from_fen(&fen).unwrap();
+
+            assert_eq!(parsed, board, "{}", fen);
+            assert_eq!(parsed.halfmove_clock, board.halfmove_clock, "{}", fen);
+            assert_eq!(parsed.fullmove_number, board.fullmove_number, "{}", fen);
+            assert_eq!(parsed.castle_rights, board.castle_rights, "{}", fen);
+            assert_eq!(parsed.en_passant_target, board.en_passant_target, "{}", fen);
+            assert_eq!(parsed.fen(), fen);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use chessr::{
+    BitBoard, Board, BoardError, CastleKind, CastleRights, CheckEscapes, Color, Move,
+    NoMovesReason, Outcome, Piece, PieceKind, PlayError, RenderStyle, SanLanguage, SanOptions,
+    SquareCoords,
+};
+
+/// Checks that every name re-exported from the crate root can be imported
+/// from there and refers to the type it is expected to.
+#[test]
+fn test_root_re_exports() {
+    let mut board = Board::new();
+    let e4 = SquareCoords::from_san_str("e4").unwrap();
+
+    let r#move: Move = board.make_move("e4").unwrap();
+    assert_eq!(
+        r#move.piece.map(|piece| piece.kind()),
+        Some(PieceKind::Pawn)
+    );
+    assert_eq!(board.squares[4][4], Some(Piece::Pawn(Color::White)));
+    assert!(board.occupied().contains(e4));
+    assert_eq!(BitBoard(0).count(), 0);
+
+    assert!(board.castle_rights.contains(&CastleRights::WhiteKingside));
+    assert_eq!(CastleKind::Kingside, CastleKind::Kingside);
+
+    let _: Option<Outcome> = board.outcome();
+    let _: Option<NoMovesReason> = board.no_moves_reason();
+    let _: Option<CheckEscapes> = None;
+    let _: Option<PlayError> = None;
+    let _: Option<BoardError> = board.validate_material().err();
+
+    let _ = board.render(RenderStyle::AsciiLetters);
+    let _ = SanLanguage::German.translate("Sf3");
+    let _ = SanOptions::default();
+}